@@ -24,3 +24,21 @@ fn bench_hash_0x20000000_z(b: &mut Bencher) {
         hasher.finish()
     });
 }
+#[bench]
+fn bench_hash_many_64_byte_records(b: &mut Bencher) {
+    let records = vec![[0x5au8; 64]; 0x1000];
+    let inputs: Vec<&[u8]> = records.iter().map(|record| &record[..]).collect();
+    let mut outputs = vec![[0u8; 32]; 0x1000];
+    b.iter(|| hash_many(&inputs, &mut outputs));
+}
+#[bench]
+fn bench_hash_64_byte_records_one_by_one(b: &mut Bencher) {
+    let records = vec![[0x5au8; 64]; 0x1000];
+    b.iter(|| {
+        for record in records.iter() {
+            let mut hasher = Sha256::new();
+            hasher.update(record);
+            test::black_box(hasher.finish());
+        }
+    });
+}
@@ -24,3 +24,18 @@ fn bench_hash_0x20000000_z(b: &mut Bencher) {
         hasher.finish()
     });
 }
+/// Raw compression of 1000 blocks.
+///
+/// The AVX2 backend is selected if built with `std` on a CPU with AVX2, or with `-C target-feature=+avx2`,
+/// and the portable one otherwise. Compare `cargo bench --bench sha512` with `--features std`.
+#[bench]
+fn bench_compress512_1000_blocks(b: &mut Bencher) {
+    let block = [0x5a; 128];
+    b.iter(|| {
+        let mut state = [0u64; 8];
+        for _ in 0..1000 {
+            compress512(&mut state, test::black_box(&block));
+        }
+        state
+    });
+}
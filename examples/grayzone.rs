@@ -1,7 +1,8 @@
 use hex_literal::hex;
-use streamsha::hash_state::{HashState, Sha256HashState};
+use streamsha::hash_state::HashState;
 use streamsha::traits::{Resumable, StreamHasher};
-use streamsha::{Sha256, Sha1};
+use streamsha::policy::{self, Policy, Purpose};
+use streamsha::{Algorithm, AnyHasher, Digest, Sha256, Sha1};
 extern crate hex_slice;
 #[macro_use]
 extern crate lazy_static;
@@ -28,7 +29,7 @@ lazy_static! {
 }
 
 /// 電子証明書の全文をハッシュ化する
-fn calculate_original_hash() -> [u8; 32] {
+fn calculate_original_hash() -> Digest<Sha256> {
     // ハッシュに必要な構造体を生成
     let mut hasher = Sha256::new();
 
@@ -36,8 +37,7 @@ fn calculate_original_hash() -> [u8; 32] {
     hasher.update(&AUTH_CERT);
 
     // 最終ブロックを計算し、ハッシュ処理を完了させ、ハッシュ値を返す
-    let hash = hasher.finish();
-    hash
+    hasher.finish_digest()
 }
 
 /// クライアントが処理すべき部分を想定している。シリアル番号を含んだデータを読んでから中断してハッシュステートを返す。入力サイズ(バイト)が64で割り切れない場合、余りの入力データがハッシュステートに含まれる。
@@ -48,12 +48,11 @@ fn calculate_client_hash() -> HashState {
     // 電子証明書の一部を入力し、ハッシュ計算
     hasher.update(&BEFORE_PUBKEY);
 
-    let state = hasher.pause();
-    state
+    hasher.pause()
 }
 
 /// ノード運用者が処理する部分を想定している。ハッシュステートを受け取って、処理を再開する。
-fn calculate_server_hash(state: HashState) -> [u8; 32] {
+fn calculate_server_hash(state: HashState) -> Digest<Sha256> {
     // 中断した時のハッシュステートを復元する
     let mut resumed = Sha256::resume(state).expect("復元失敗！");
 
@@ -64,21 +63,24 @@ fn calculate_server_hash(state: HashState) -> [u8; 32] {
     resumed.update(&AFTER_PUBKEY);
 
     // 最終ブロックを計算し、ハッシュ処理を完了させ、ハッシュ値を返す
-    let hash = resumed.finish();
-    hash
+    resumed.finish_digest()
 }
 
-/// 公開鍵のSHA-1ハッシュを求める
-fn calculate_pubkey_sha1() -> [u8; 20] {
-    let mut hasher = Sha1::new();
+/// 公開鍵のSHA-1ハッシュを求める。SHA-1はSubjectKeyIdentifierの照合だけに使う。
+fn calculate_pubkey_sha1() -> Digest<Sha1> {
+    let mut hasher = Sha1::with_purpose(Purpose::KeyIdentifier).expect("ポリシー違反！");
     hasher.update(&PUBKEY);
-    let hash = hasher.finish();
-    hash
+    hasher.finish_digest()
 }
 
 fn main() {
+    // 承認されたアルゴリズムのみ許可する
+    policy::set(Policy::FipsApproved);
+    if let Err(e) = AnyHasher::with_purpose(Algorithm::Sha1, Purpose::DigitalSignature) {
+        println!("署名用のSHA-1は拒否されました: {}", e);
+    }
     let original = calculate_original_hash();
-    println!("全文のハッシュ値: {:x?}", original);
+    println!("全文のハッシュ値: {}", original);
     let client_hashstate = calculate_client_hash();
     if let HashState::Sha256(client_state) = &client_hashstate {
         println!("出力されたハッシュステート: {{");
@@ -93,7 +95,7 @@ fn main() {
         panic!("SHA-256ステートではありません。失敗です。");
     }
     let server_hash = calculate_server_hash(client_hashstate);
-    println!("ノード運用者が計算したハッシュ: {:x?}", original);
+    println!("ノード運用者が計算したハッシュ: {}", server_hash);
     if original == server_hash {
         println!("ハッシュ値が一致しました。成功です。");
     } else {
@@ -102,8 +104,8 @@ fn main() {
 
     println!("--------");
     let pubkeyhash = calculate_pubkey_sha1();
-    println!("公開鍵SHA-1ハッシュ: {:x?}", pubkeyhash);
-    if &pubkeyhash[..] == &SUBJ_KEY_ID[..] {
+    println!("公開鍵SHA-1ハッシュ: {}", pubkeyhash);
+    if pubkeyhash == SUBJ_KEY_ID[..] {
         println!("公開鍵ハッシュが一致しました。成功です。");
     } else {
         println!("公開鍵ハッシュが一致しません。失敗です。");
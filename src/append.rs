@@ -0,0 +1,255 @@
+//! Incremental re-hashing of append-only files
+//!
+//! `AppendRehasher` stores the paused `HashState` of a file in a sidecar, with the file length and metadata.
+//! On the next call, if the file still starts with the hashed prefix, only the appended bytes are hashed.
+//!
+//! The prefix is considered unchanged if the file is not shorter, has the same device and inode (on unix),
+//! and its mtime has not gone backwards, or is unchanged if the length is. Optionally, the last bytes of the prefix are
+//! re-read and compared with their SHA-256 recorded in the sidecar, to catch in-place rewrites.
+//! Otherwise, or if the sidecar is missing, corrupted or of another hash, the whole file is hashed.
+//! If resuming fails with `PolicyViolation`, it's returned as a `PermissionDenied` error instead.
+//!
+//! Only available with the `std` feature.
+//!
+//! ```rust,no_run
+//! use streamsha::append::AppendRehasher;
+//! use streamsha::Sha256;
+//!
+//! let rehasher = AppendRehasher::new("app.log").with_spot_check(4096);
+//! let rehash = rehasher.rehash::<Sha256>()?;
+//! println!("{} bytes, {} newly hashed", rehash.len, rehash.hashed);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::sidecar;
+use crate::hash_state;
+use crate::hash_state::HashState;
+use crate::traits::*;
+use crate::Sha256;
+use std::ffi::OsString;
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use std::vec::Vec;
+
+/// Magic bytes and version of the sidecar file
+const MAGIC: &[u8; 5] = b"SSAP\x01";
+const HEADER_SIZE: usize = 8 + 8 + 8 + 8 + 4 + 8 + 32;
+
+/// Re-hashes a file, hashing only the bytes appended since the last call
+pub struct AppendRehasher {
+    path: PathBuf,
+    sidecar: PathBuf,
+    spot_check: u64,
+}
+
+/// The result of `AppendRehasher::rehash`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rehash<D> {
+    /// The hash of the whole file
+    pub digest: D,
+    /// The length of the file in bytes
+    pub len: u64,
+    /// The number of bytes read and hashed by this call
+    pub hashed: u64,
+}
+
+/// Identity and modification time of a file
+#[derive(PartialEq)]
+struct FileId {
+    dev: u64,
+    ino: u64,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+}
+
+impl FileId {
+    fn of(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        let (dev, ino) = {
+            use std::os::unix::fs::MetadataExt;
+            (metadata.dev(), metadata.ino())
+        };
+        #[cfg(not(unix))]
+        let (dev, ino) = (0, 0);
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        Self {
+            dev,
+            ino,
+            mtime_secs: mtime.as_secs(),
+            mtime_nanos: mtime.subsec_nanos(),
+        }
+    }
+    fn mtime(&self) -> (u64, u32) {
+        (self.mtime_secs, self.mtime_nanos)
+    }
+}
+
+/// The content of the sidecar
+struct Stored {
+    len: u64,
+    id: FileId,
+    spot_len: u64,
+    spot_digest: [u8; 32],
+    state: HashState,
+}
+
+impl Stored {
+    fn parse(content: &[u8]) -> Option<Self> {
+        if content.len() < HEADER_SIZE {
+            return None;
+        }
+        let u64_at = |pos: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&content[pos..pos + 8]);
+            u64::from_be_bytes(bytes)
+        };
+        let mut mtime_nanos = [0u8; 4];
+        mtime_nanos.copy_from_slice(&content[32..36]);
+        let mut spot_digest = [0u8; 32];
+        spot_digest.copy_from_slice(&content[44..76]);
+        Some(Self {
+            len: u64_at(0),
+            id: FileId {
+                dev: u64_at(8),
+                ino: u64_at(16),
+                mtime_secs: u64_at(24),
+                mtime_nanos: u32::from_be_bytes(mtime_nanos),
+            },
+            spot_len: u64_at(36),
+            spot_digest,
+            state: sidecar::parse_state(&content[HEADER_SIZE..])?,
+        })
+    }
+    fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + 2 + HashState::MAX_ENCODED_LEN);
+        bytes.extend_from_slice(&self.len.to_be_bytes());
+        bytes.extend_from_slice(&self.id.dev.to_be_bytes());
+        bytes.extend_from_slice(&self.id.ino.to_be_bytes());
+        bytes.extend_from_slice(&self.id.mtime_secs.to_be_bytes());
+        bytes.extend_from_slice(&self.id.mtime_nanos.to_be_bytes());
+        bytes.extend_from_slice(&self.spot_len.to_be_bytes());
+        bytes.extend_from_slice(&self.spot_digest);
+        sidecar::push_state(&mut bytes, &self.state)?;
+        Ok(bytes)
+    }
+}
+
+impl AppendRehasher {
+    /// Create new instance re-hashing `path`, with the sidecar at `<path>.hashstate`
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = PathBuf::from(path.as_ref());
+        let mut sidecar = OsString::from(path.as_os_str());
+        sidecar.push(".hashstate");
+        Self {
+            path,
+            sidecar: PathBuf::from(sidecar),
+            spot_check: 0,
+        }
+    }
+    /// Stores the state at `sidecar` instead
+    pub fn with_sidecar(mut self, sidecar: impl AsRef<Path>) -> Self {
+        self.sidecar = PathBuf::from(sidecar.as_ref());
+        self
+    }
+    /// Re-reads up to `len` bytes at the end of the hashed prefix, to verify it's unchanged. Disabled by default.
+    pub fn with_spot_check(mut self, len: u64) -> Self {
+        self.spot_check = len;
+        self
+    }
+    /// Hashes the file, resuming from the sidecar if the prefix is unchanged, and updates the sidecar.
+    ///
+    /// Bytes appended while hashing are left for the next call.
+    pub fn rehash<H>(&self) -> io::Result<Rehash<H::Output>>
+    where
+        H: StreamHasher + Resumable + Clone + Default,
+    {
+        let mut file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+        let len = metadata.len();
+        let id = FileId::of(&metadata);
+
+        let stored = sidecar::read(&self.sidecar, MAGIC).ok().flatten().and_then(|content| Stored::parse(&content));
+        let (mut hasher, start) = match stored {
+            Some(stored) if self.is_prefix(&stored, len, &id, &mut file)? => match H::resume(stored.state) {
+                Ok(hasher) => (hasher, stored.len),
+                Err(hash_state::Error::PolicyViolation(e)) => {
+                    return Err(io::Error::new(io::ErrorKind::PermissionDenied, e))
+                }
+                Err(_) => (H::default(), 0),
+            },
+            _ => (H::default(), 0),
+        };
+
+        file.seek(SeekFrom::Start(start))?;
+        let mut reader = (&mut file).take(len - start);
+        let mut buf = [0u8; 64 * 1024];
+        let mut hashed = 0u64;
+        loop {
+            let read_len = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read_len) => read_len,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            hasher.update(&buf[..read_len]);
+            hashed += read_len as u64;
+        }
+        if hashed != len - start {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "file truncated while hashing"));
+        }
+
+        let spot_len = self.spot_check.min(len);
+        let spot_digest = if spot_len != 0 { window_digest(&mut file, len, spot_len)? } else { [0; 32] };
+        let stored = Stored {
+            len,
+            id,
+            spot_len,
+            spot_digest,
+            state: hasher.clone().pause(),
+        };
+        sidecar::write(&self.sidecar, MAGIC, &stored.to_bytes()?)?;
+        Ok(Rehash {
+            digest: hasher.finish(),
+            len,
+            hashed,
+        })
+    }
+    /// Whether the file still starts with the prefix hashed into `stored`
+    fn is_prefix(&self, stored: &Stored, len: u64, id: &FileId, file: &mut File) -> io::Result<bool> {
+        let unchanged = stored.len <= len
+            && (stored.id.dev, stored.id.ino) == (id.dev, id.ino)
+            && if stored.len == len { stored.id.mtime() == id.mtime() } else { stored.id.mtime() <= id.mtime() };
+        if !unchanged || stored.spot_len > stored.len {
+            return Ok(false);
+        }
+        if stored.spot_len == 0 {
+            return Ok(true);
+        }
+        Ok(window_digest(file, stored.len, stored.spot_len)? == stored.spot_digest)
+    }
+}
+
+/// SHA-256 of the `len` bytes of `file` before `end`
+fn window_digest(file: &mut File, end: u64, len: u64) -> io::Result<[u8; 32]> {
+    file.seek(SeekFrom::Start(end - len))?;
+    let mut hasher = Sha256::new();
+    let mut reader = file.take(len);
+    let mut buf = [0u8; 8 * 1024];
+    let mut hashed = 0u64;
+    while hashed < len {
+        let read_len = match reader.read(&mut buf) {
+            Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "file truncated while hashing")),
+            Ok(read_len) => read_len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..read_len]);
+        hashed += read_len as u64;
+    }
+    Ok(hasher.finish())
+}
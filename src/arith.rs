@@ -58,6 +58,19 @@ macro_rules! define_word {
                 Self(i)
             }
         }
+
+        impl $word {
+            /// Unwraps an array of words, in const context
+            pub const fn unwrap_array<const N: usize>(words: [Self; N]) -> [$t; N] {
+                let mut raw = [0; N];
+                let mut i = 0;
+                while i < N {
+                    raw[i] = words[i].0;
+                    i += 1;
+                }
+                raw
+            }
+        }
     };
 }
 
@@ -65,6 +78,7 @@ define_word!(Word32, u32);
 define_word!(Word64, u64);
 
 /// Performs circular right shift
+#[allow(dead_code)]
 pub fn rotr<T: Shl<usize, Output = T> + Shr<usize, Output = T> + BitOr<Output = T> + Copy>(
     x: T,
     n: usize,
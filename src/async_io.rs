@@ -0,0 +1,187 @@
+//! Async hashing adapters for tokio and futures-io
+//!
+//! `AsyncHashingReader` and `AsyncHashingWriter` hash data while passing it through, and every hasher implements `AsyncWrite`.
+//! The adapters implement the tokio traits with the `tokio` feature, and the futures-io traits with the `futures-io` feature.
+//!
+//! Data is hashed in the same poll it's passed through, so when a future using an adapter is cancelled,
+//! `pause` returns the state of exactly the data that has been passed. An interrupted upload can persist it
+//! and resume hashing from there, instead of from byte 0.
+//!
+//! The inner reader / writer must be `Unpin`; pin it with `Box::pin` otherwise.
+use crate::dynamic::AnyHasher;
+use crate::hash_state::HashState;
+use crate::md::{MdCompression, MdHasher};
+use crate::traits::*;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::io;
+
+/// A reader that hashes the data read from the inner reader
+pub struct AsyncHashingReader<R, H: StreamHasher> {
+    inner: R,
+    hasher: H,
+}
+
+/// A writer that hashes the data written to the inner writer
+pub struct AsyncHashingWriter<W, H: StreamHasher> {
+    inner: W,
+    hasher: H,
+}
+
+macro_rules! impl_adapter {
+    ($name:ident, $inner:ident, $doc:literal) => {
+        impl<$inner, H: StreamHasher> $name<$inner, H> {
+            #[doc = $doc]
+            pub fn new(inner: $inner, hasher: H) -> Self {
+                Self { inner, hasher }
+            }
+            /// Returns the hasher
+            pub fn hasher(&self) -> &H {
+                &self.hasher
+            }
+            /// Returns the hash state of the data passed through so far, e.g. after the future using it was cancelled.
+            pub fn pause(&self) -> HashState
+            where
+                H: Resumable + Clone,
+            {
+                self.hasher.clone().pause()
+            }
+            /// Unwraps the inner stream and the hasher
+            pub fn into_parts(self) -> ($inner, H) {
+                (self.inner, self.hasher)
+            }
+            /// Drops the inner stream and outputs the final hash of the data passed through so far.
+            pub fn finish(self) -> H::Output {
+                self.hasher.finish()
+            }
+        }
+    };
+}
+
+impl_adapter!(AsyncHashingReader, R, "Create new instance reading from `inner` into `hasher`");
+impl_adapter!(AsyncHashingWriter, W, "Create new instance writing to `inner` and hashing into `hasher`");
+
+#[cfg(feature = "tokio")]
+mod tokio_impl {
+    use super::*;
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    impl<R: AsyncRead + Unpin, H: StreamHasher + Unpin> AsyncRead for AsyncHashingReader<R, H> {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            let filled_len = buf.filled().len();
+            let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+            if let Poll::Ready(Ok(())) = result {
+                this.hasher.update(&buf.filled()[filled_len..]);
+            }
+            result
+        }
+    }
+
+    impl<W: AsyncWrite + Unpin, H: StreamHasher + Unpin> AsyncWrite for AsyncHashingWriter<W, H> {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+            if let Poll::Ready(Ok(len)) = result {
+                // only the bytes accepted by the inner writer
+                this.hasher.update(&buf[..len]);
+            }
+            result
+        }
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+        }
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+        }
+    }
+
+    impl<C: MdCompression> AsyncWrite for MdHasher<C>
+    where
+        Self: Unpin,
+    {
+        fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(Ok(StreamHasher::update(self.get_mut(), buf)))
+        }
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncWrite for AnyHasher {
+        fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(Ok(self.get_mut().update(buf)))
+        }
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+#[cfg(feature = "futures-io")]
+mod futures_impl {
+    use super::*;
+    use futures_io::{AsyncRead, AsyncWrite};
+
+    impl<R: AsyncRead + Unpin, H: StreamHasher + Unpin> AsyncRead for AsyncHashingReader<R, H> {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+            if let Poll::Ready(Ok(len)) = result {
+                this.hasher.update(&buf[..len]);
+            }
+            result
+        }
+    }
+
+    impl<W: AsyncWrite + Unpin, H: StreamHasher + Unpin> AsyncWrite for AsyncHashingWriter<W, H> {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+            if let Poll::Ready(Ok(len)) = result {
+                // only the bytes accepted by the inner writer
+                this.hasher.update(&buf[..len]);
+            }
+            result
+        }
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+        }
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_close(cx)
+        }
+    }
+
+    impl<C: MdCompression> AsyncWrite for MdHasher<C>
+    where
+        Self: Unpin,
+    {
+        fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(Ok(StreamHasher::update(self.get_mut(), buf)))
+        }
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncWrite for AnyHasher {
+        fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(Ok(self.get_mut().update(buf)))
+        }
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
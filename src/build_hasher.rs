@@ -0,0 +1,190 @@
+//! `core::hash` integration for deterministic map hashing
+//!
+//! `StreamshaBuildHasher` builds `StreamshaHasher`s, which implement `core::hash::Hasher` over an MD hash
+//! (SHA-256 by default). Unlike `std`'s `RandomState`, the hashes of bytes and integers are the same across
+//! processes and machines, so they can be used for persisted indexes. Other types are hashed by their `Hash` impls,
+//! and `std`'s aren't guaranteed to stay the same across Rust versions. With a key, the data is hashed with HMAC, so the hashes can't be
+//! predicted (and collided) without the key.
+//!
+//! `finish` returns the first 8 bytes of the digest as a big endian `u64`, zero-padded for shorter digests. Integers are hashed as little endian,
+//! and `usize` / `isize` as 64 bit, independent of the platform.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use streamsha::build_hasher::StreamshaBuildHasher;
+//!
+//! let mut map = HashMap::with_hasher(StreamshaBuildHasher::<streamsha::Sha512Compression>::with_key(b"shard key"));
+//! map.insert("abc", 1);
+//! assert_eq!(map["abc"], 1);
+//! ```
+use crate::md::{MdCompression, MdHasher};
+use crate::policy::{self, PolicyViolation, Purpose};
+use crate::sha256::Sha256Compression;
+use crate::traits::StreamHasher;
+use core::hash::{BuildHasher, Hasher};
+
+/// Builds `StreamshaHasher`s, optionally keyed
+pub struct StreamshaBuildHasher<C: MdCompression = Sha256Compression> {
+    inner: MdHasher<C>,
+    outer: Option<MdHasher<C>>,
+}
+
+impl<C: MdCompression> StreamshaBuildHasher<C>
+where
+    C::Output: AsRef<[u8]>,
+{
+    /// Create new unkeyed instance, hashing the plain data
+    ///
+    /// Panics if the current policy forbids the hash for `Purpose::Integrity`, see `try_new`.
+    pub fn new() -> Self {
+        Self::try_new().unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Create new unkeyed instance, or fail if the current policy forbids the hash for `Purpose::Integrity`
+    pub fn try_new() -> Result<Self, PolicyViolation> {
+        check_policy::<C>(Purpose::Integrity)?;
+        Ok(Self {
+            inner: MdHasher::new(),
+            outer: None,
+        })
+    }
+    /// Create new instance hashing the data with HMAC under `key`
+    ///
+    /// Panics if the current policy forbids the hash for `Purpose::Mac`, see `try_with_key`.
+    pub fn with_key(key: &[u8]) -> Self {
+        Self::try_with_key(key).unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Create new keyed instance, or fail if the current policy forbids the hash for `Purpose::Mac`
+    pub fn try_with_key(key: &[u8]) -> Result<Self, PolicyViolation> {
+        check_policy::<C>(Purpose::Mac)?;
+        let mut key_block = C::EMPTY_BLOCK;
+        if key.len() > C::BLOCK_SIZE {
+            let mut hasher = MdHasher::<C>::new();
+            hasher.update(key);
+            let digest = hasher.finish();
+            key_block.as_mut()[..digest.as_ref().len()].copy_from_slice(digest.as_ref());
+        } else {
+            key_block.as_mut()[..key.len()].copy_from_slice(key);
+        }
+        let mut pad = key_block;
+        for byte in pad.as_mut() {
+            *byte ^= 0x36;
+        }
+        let mut inner = MdHasher::new();
+        inner.update(pad.as_ref());
+        for byte in pad.as_mut() {
+            *byte ^= 0x36 ^ 0x5c;
+        }
+        let mut outer = MdHasher::new();
+        outer.update(pad.as_ref());
+        Ok(Self {
+            inner,
+            outer: Some(outer),
+        })
+    }
+}
+
+fn check_policy<C: MdCompression>(purpose: Purpose) -> Result<(), PolicyViolation> {
+    policy::current().check(policy::algorithm_of::<C>(), purpose)
+}
+
+impl<C: MdCompression> Clone for StreamshaBuildHasher<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            outer: self.outer.clone(),
+        }
+    }
+}
+
+impl<C: MdCompression> Default for StreamshaBuildHasher<C>
+where
+    C::Output: AsRef<[u8]>,
+{
+    /// Same as `new`, so panics if the current policy forbids the hash for `Purpose::Integrity`
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: MdCompression> BuildHasher for StreamshaBuildHasher<C>
+where
+    C::Output: AsRef<[u8]>,
+{
+    type Hasher = StreamshaHasher<C>;
+    fn build_hasher(&self) -> StreamshaHasher<C> {
+        StreamshaHasher {
+            inner: self.inner.clone(),
+            outer: self.outer.clone(),
+        }
+    }
+}
+
+/// `core::hash::Hasher` over an MD hash, built by `StreamshaBuildHasher`
+pub struct StreamshaHasher<C: MdCompression = Sha256Compression> {
+    inner: MdHasher<C>,
+    outer: Option<MdHasher<C>>,
+}
+
+impl<C: MdCompression> Clone for StreamshaHasher<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            outer: self.outer.clone(),
+        }
+    }
+}
+
+impl<C: MdCompression> Hasher for StreamshaHasher<C>
+where
+    C::Output: AsRef<[u8]>,
+{
+    fn finish(&self) -> u64 {
+        let digest = self.inner.clone().finish();
+        let digest = match &self.outer {
+            Some(outer) => {
+                let mut outer = outer.clone();
+                outer.update(digest.as_ref());
+                outer.finish()
+            }
+            None => digest,
+        };
+        let digest = digest.as_ref();
+        let len = digest.len().min(8);
+        let mut bytes = [0u8; 8];
+        bytes[..len].copy_from_slice(&digest[..len]);
+        u64::from_be_bytes(bytes)
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.update(bytes);
+    }
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+    fn write_i16(&mut self, i: i16) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_i32(&mut self, i: i32) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_i128(&mut self, i: i128) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
@@ -0,0 +1,182 @@
+//! Crash-safe checkpointing of long-running hashes
+//!
+//! `CheckpointingHasher` periodically persists the hash state and the input offset to a sidecar file,
+//! and `resume_from_checkpoint` continues from it after a restart, seeking the input to the recorded offset.
+//!
+//! The sidecar is written to `<path>.tmp`, synced and atomically renamed to `<path>`, so it always holds a
+//! complete checkpoint. It consists of `SSCP`, version 1, the offset (u64 BE), the length of the encoded
+//! `HashState` (u16 BE), the encoded `HashState`, and the SHA-256 of all preceding bytes.
+//!
+//! Only available with the `std` feature.
+//!
+//! ```rust,no_run
+//! use std::fs::File;
+//! use std::io;
+//! use streamsha::checkpoint::{resume_from_checkpoint, CheckpointingHasher};
+//! use streamsha::Sha256;
+//!
+//! let mut archive = File::open("archive.tar")?;
+//! let mut hasher = match resume_from_checkpoint::<Sha256, _>("archive.tar.ckpt", &mut archive) {
+//!     Ok(hasher) => hasher,
+//!     Err(e) if e.kind() == io::ErrorKind::NotFound => CheckpointingHasher::new(Sha256::new(), "archive.tar.ckpt"),
+//!     Err(e) => return Err(e),
+//! };
+//! io::copy(&mut archive, &mut hasher)?;
+//! let digest = hasher.finish()?;
+//! # Ok::<(), io::Error>(())
+//! ```
+use crate::hash_state;
+use crate::hash_state::HashState;
+use crate::sidecar;
+use crate::traits::*;
+use core::convert::TryFrom;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+/// Magic bytes and version of the sidecar file
+const MAGIC: &[u8; 5] = b"SSCP\x01";
+
+/// A persisted hash state and the input offset it was taken at
+pub struct Checkpoint {
+    /// The number of input bytes hashed into `state`
+    pub offset: u64,
+    pub state: HashState,
+}
+
+impl Checkpoint {
+    /// Reads a checkpoint from `path`, failing with `InvalidData` if it's corrupted.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let content = sidecar::read(path.as_ref(), MAGIC)?.ok_or_else(invalid_checkpoint)?;
+        if content.len() < 10 {
+            return Err(invalid_checkpoint());
+        }
+        let mut offset = [0u8; 8];
+        offset.copy_from_slice(&content[..8]);
+        Ok(Self {
+            offset: u64::from_be_bytes(offset),
+            state: sidecar::parse_state(&content[8..]).ok_or_else(invalid_checkpoint)?,
+        })
+    }
+    /// Atomically writes the checkpoint to `path`, through `<path>.tmp`. Fails with `InvalidInput` if the state
+    /// can't be encoded.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut content = Vec::with_capacity(10 + HashState::MAX_ENCODED_LEN);
+        content.extend_from_slice(&self.offset.to_be_bytes());
+        sidecar::push_state(&mut content, &self.state)?;
+        sidecar::write(path.as_ref(), MAGIC, &content)
+    }
+}
+
+fn invalid_checkpoint() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid checkpoint")
+}
+
+/// A hasher that persists its state to a sidecar file every `interval` bytes
+pub struct CheckpointingHasher<H: StreamHasher + Resumable + Clone> {
+    hasher: H,
+    path: PathBuf,
+    offset: u64,
+    interval: u64,
+    checkpointed_offset: u64,
+}
+
+impl<H: StreamHasher + Resumable + Clone> CheckpointingHasher<H> {
+    /// The default checkpoint interval, 1 GiB
+    pub const DEFAULT_INTERVAL: u64 = 1 << 30;
+
+    /// Create new instance hashing from offset 0 into `hasher`, checkpointing to `path`
+    pub fn new(hasher: H, path: impl AsRef<Path>) -> Self {
+        Self::with_offset(hasher, path, 0)
+    }
+    fn with_offset(hasher: H, path: impl AsRef<Path>, offset: u64) -> Self {
+        Self {
+            hasher,
+            path: PathBuf::from(path.as_ref()),
+            offset,
+            interval: Self::DEFAULT_INTERVAL,
+            checkpointed_offset: offset,
+        }
+    }
+    /// Sets the number of bytes between checkpoints, at least 1
+    pub fn with_interval(mut self, interval: u64) -> Self {
+        self.interval = interval.max(1);
+        self
+    }
+    /// Returns the number of input bytes hashed so far, including the ones before the checkpoint resumed from
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// Returns the hasher
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+    /// Write to buffer, writing a checkpoint every `interval` bytes.
+    ///
+    /// If writing a checkpoint fails, returns the number of bytes hashed before it, or the error if there are none.
+    /// Bytes are never hashed without being counted, so the caller can retry with the rest, as with `Write::write`.
+    pub fn update(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            if self.offset - self.checkpointed_offset >= self.interval {
+                if let Err(e) = self.checkpoint() {
+                    return if written == 0 { Err(e) } else { Ok(written) };
+                }
+            }
+            let until_checkpoint = self.interval - (self.offset - self.checkpointed_offset);
+            let len = (buf.len() - written).min(usize::try_from(until_checkpoint).unwrap_or(usize::MAX));
+            self.hasher.update(&buf[written..written + len]);
+            self.offset += len as u64;
+            written += len;
+        }
+        Ok(written)
+    }
+    /// Writes a checkpoint of the current state now
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        Checkpoint {
+            offset: self.offset,
+            state: self.hasher.clone().pause(),
+        }
+        .write(&self.path)?;
+        self.checkpointed_offset = self.offset;
+        Ok(())
+    }
+    /// Outputs the final hash and removes the sidecar file.
+    pub fn finish(self) -> io::Result<H::Output> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        Ok(self.hasher.finish())
+    }
+}
+
+impl<H: StreamHasher + Resumable + Clone> Write for CheckpointingHasher<H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Resumes hashing from the checkpoint at `path`, seeking `reader` to the recorded offset.
+///
+/// Fails with `NotFound` if there is no checkpoint, with `InvalidData` if it's corrupted or of another hash,
+/// and with `PermissionDenied` if resuming fails with `PolicyViolation`.
+pub fn resume_from_checkpoint<H, R>(path: impl AsRef<Path>, reader: &mut R) -> io::Result<CheckpointingHasher<H>>
+where
+    H: StreamHasher + Resumable + Clone,
+    R: Read + Seek,
+{
+    let path = path.as_ref();
+    let checkpoint = Checkpoint::read(path)?;
+    let hasher = H::resume(checkpoint.state).map_err(|e| match e {
+        hash_state::Error::HashTypeNotMatch => io::Error::new(io::ErrorKind::InvalidData, "checkpoint of another hash"),
+        hash_state::Error::PolicyViolation(e) => io::Error::new(io::ErrorKind::PermissionDenied, e),
+        _ => invalid_checkpoint(),
+    })?;
+    reader.seek(SeekFrom::Start(checkpoint.offset))?;
+    Ok(CheckpointingHasher::with_offset(hasher, path, checkpoint.offset))
+}
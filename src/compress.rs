@@ -0,0 +1,27 @@
+//! Raw compression functions
+//!
+//! These process a single block without any padding or length bookkeeping, for custom constructions
+//! and midstate computation. They use the same backends as the hashers.
+use crate::consts::*;
+use crate::self_test;
+use crate::sha1::Sha1;
+use crate::sha256::Sha256;
+use crate::sha512::Sha512;
+
+/// Applies the SHA-1 compression function to `state` with `block`
+pub fn compress_sha1(state: &mut [u32; 5], block: &[u8; SHA1_BLOCK_SIZE]) {
+    self_test::ensure();
+    Sha1::compress_blocks(state, core::slice::from_ref(block));
+}
+
+/// Applies the SHA-256 compression function to `state` with `block`
+pub fn compress256(state: &mut [u32; 8], block: &[u8; SHA256_BLOCK_SIZE]) {
+    self_test::ensure();
+    Sha256::compress_blocks(state, core::slice::from_ref(block));
+}
+
+/// Applies the SHA-512 compression function to `state` with `block`
+pub fn compress512(state: &mut [u64; 8], block: &[u8; SHA512_BLOCK_SIZE]) {
+    self_test::ensure();
+    Sha512::compress_blocks(state, core::slice::from_ref(block));
+}
@@ -14,8 +14,14 @@ pub const SHA256_K: [Word32; 64] = arr32![
 pub const SHA256_H: [Word32; 8] = arr32![
     0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
 ];
+
+pub const SHA224_H: [Word32; 8] = arr32![
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
 // 512 bit SHA-256 block
 pub const SHA256_BLOCK_SIZE: usize = 64;
+// (2^64 - 1) bit message, in bytes
+pub const SHA256_MAX_MESSAGE_LEN: u64 = u64::MAX / 8;
 
 pub const SHA512_K: [Word64; 80] = arr64![
     0x428a2f98d728ae22,
@@ -110,8 +116,43 @@ pub const SHA512_H: [Word64; 8] = arr64![
     0x1f83d9abfb41bd6b,
     0x5be0cd19137e2179
 ];
+
+pub const SHA384_H: [Word64; 8] = arr64![
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4
+];
+
+pub const SHA512_224_H: [Word64; 8] = arr64![
+    0x8c3d37c819544da2,
+    0x73e1996689dcd4d6,
+    0x1dfab7ae32ff9c82,
+    0x679dd514582f9fcf,
+    0x0f6d2b697bd44da8,
+    0x77e36f7304c48942,
+    0x3f9d85a86a1d36c8,
+    0x1112e6ad91d692a1
+];
+
+pub const SHA512_256_H: [Word64; 8] = arr64![
+    0x22312194fc2bf72c,
+    0x9f555fa3c84c64c2,
+    0x2393b86b6f53b151,
+    0x963877195940eabd,
+    0x96283ee2a88effe3,
+    0xbe5e1e2553863992,
+    0x2b0199fc2c85b8aa,
+    0x0eb72ddc81c52ca2
+];
 // 1024 bit SHA-512 block
 pub const SHA512_BLOCK_SIZE: usize = 128;
+// (2^128 - 1) bit message, in bytes
+pub const SHA512_MAX_MESSAGE_LEN: u128 = u128::MAX / 8;
 
 pub const SHA1_H: [Word32; 5] = arr32![
     0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0
@@ -124,7 +165,13 @@ pub const fn SHA1_K(t: usize) -> Word32 {
         0x6ed9eba1,
         0x8f1bbcdc,
         0xca62c1d6
-    ])[t/20 as usize]
+    ])[t / 20]
 }
 
 pub const SHA1_BLOCK_SIZE: usize = 64;
+// (2^64 - 1) bit message, in bytes
+pub const SHA1_MAX_MESSAGE_LEN: u64 = u64::MAX / 8;
+
+// The max sizes of a generic `MdHasher` that can be paused
+pub const MD_MAX_STATE_SIZE: usize = 128;
+pub const MD_MAX_BLOCK_SIZE: usize = 256;
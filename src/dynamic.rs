@@ -0,0 +1,292 @@
+//! Runtime algorithm selection
+//!
+//! `StreamHasher` is generic over the output type, so the algorithm must be known on compile time.
+//! `AnyHasher` selects it on runtime instead, e.g. from a config or a certificate OID.
+//! Both `AnyHasher` and the hashers implement the object-safe `DynHasher`.
+//!
+//! ```rust
+//! use streamsha::{Algorithm, AnyHasher};
+//!
+//! let algorithm = Algorithm::from_name("sha256").unwrap();
+//! let mut hasher = AnyHasher::new(algorithm).unwrap();
+//! hasher.update(b"abc");
+//! let mut digest = [0u8; Algorithm::MAX_OUTPUT_SIZE];
+//! let len = hasher.finish_into(&mut digest).unwrap();
+//! assert_eq!(digest[..len], streamsha::sha256(b"abc"));
+//! ```
+use crate::hash_state;
+use crate::hash_state::HashState;
+use crate::md::{MdCompression, MdHasher};
+use crate::output::BufferTooSmall;
+use crate::policy::{self, PolicyViolation};
+use crate::self_test;
+use crate::traits::*;
+use core::ops::Deref;
+use crate::{Sha1, Sha256, Sha512};
+use core::fmt;
+use core::mem::size_of;
+use core::iter::FromIterator;
+
+/// Hash algorithms that can be selected on runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    /// All algorithms
+    pub const ALL: [Algorithm; 3] = [Algorithm::Sha1, Algorithm::Sha256, Algorithm::Sha512];
+    /// The largest output size of all algorithms in bytes
+    pub const MAX_OUTPUT_SIZE: usize = 64;
+
+    /// Returns the name, e.g. `SHA-256`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha1 => "SHA-1",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Sha512 => "SHA-512",
+        }
+    }
+    /// Parses a name. Case and hyphens are ignored, so `SHA-256`, `sha256` and `Sha-256` are accepted.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized = |s: &str| {
+            s.bytes()
+                .filter(|&b| b != b'-')
+                .map(|b| b.to_ascii_lowercase())
+                .collect::<NameBytes>()
+        };
+        let name = normalized(name);
+        Self::ALL.iter().copied().find(|algorithm| normalized(algorithm.name()) == name)
+    }
+    /// Returns the object identifier in dotted notation, e.g. `2.16.840.1.101.3.4.2.1`.
+    pub const fn oid(self) -> &'static str {
+        match self {
+            Algorithm::Sha1 => "1.3.14.3.2.26",
+            Algorithm::Sha256 => "2.16.840.1.101.3.4.2.1",
+            Algorithm::Sha512 => "2.16.840.1.101.3.4.2.3",
+        }
+    }
+    /// Parses an object identifier in dotted notation.
+    pub fn from_oid(oid: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|algorithm| algorithm.oid() == oid)
+    }
+    /// Returns the DER encoded object identifier, without tag and length.
+    pub const fn oid_der(self) -> &'static [u8] {
+        match self {
+            Algorithm::Sha1 => &[0x2b, 0x0e, 0x03, 0x02, 0x1a],
+            Algorithm::Sha256 => &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01],
+            Algorithm::Sha512 => &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03],
+        }
+    }
+    /// Parses a DER encoded object identifier, without tag and length.
+    pub fn from_oid_der(oid: &[u8]) -> Option<Self> {
+        Self::ALL.iter().copied().find(|algorithm| algorithm.oid_der() == oid)
+    }
+    /// Returns the algorithm of `hash_state`, or `None` for generic `HashState::Md` and composite `HashState::Multi`.
+    pub fn from_hash_state(hash_state: &HashState) -> Option<Self> {
+        match hash_state {
+            HashState::Sha1(_) => Some(Algorithm::Sha1),
+            HashState::Sha256(_) => Some(Algorithm::Sha256),
+            HashState::Sha512(_) => Some(Algorithm::Sha512),
+            HashState::Md(_) | HashState::Multi(_) => None,
+        }
+    }
+    /// Returns the hash state of an empty message.
+    pub fn initial_state(self) -> HashState {
+        AnyHasher::new_unchecked(self).pause()
+    }
+    /// Output size in bytes
+    pub const fn output_size(self) -> usize {
+        match self {
+            Algorithm::Sha1 => 20,
+            Algorithm::Sha256 => 32,
+            Algorithm::Sha512 => 64,
+        }
+    }
+    /// Block size in bytes
+    pub const fn block_size(self) -> usize {
+        match self {
+            Algorithm::Sha1 => Sha1::BLOCK_SIZE,
+            Algorithm::Sha256 => Sha256::BLOCK_SIZE,
+            Algorithm::Sha512 => Sha512::BLOCK_SIZE,
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Up to 16 bytes of a normalized name, for comparison without allocation
+#[derive(PartialEq)]
+struct NameBytes {
+    bytes: [u8; 16],
+    len: usize,
+}
+
+impl FromIterator<u8> for NameBytes {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut name = NameBytes { bytes: [0; 16], len: 0 };
+        for b in iter {
+            if name.len == name.bytes.len() {
+                // too long to be any name; never equal to a valid one
+                name.len = usize::MAX;
+                break;
+            }
+            name.bytes[name.len] = b;
+            name.len += 1;
+        }
+        name
+    }
+}
+
+/// Object-safe interface of hashers, usable as `dyn DynHasher`
+///
+/// `update` has the same name as `StreamHasher::update`; with both traits in scope, call it on a concrete hasher as `StreamHasher::update(&mut hasher, buf)`.
+pub trait DynHasher {
+    /// Output size in bytes
+    fn output_size(&self) -> usize;
+    /// Write to buffer. See `StreamHasher::update`.
+    fn update(&mut self, buf: &[u8]) -> usize;
+    /// Writes the final hash into `out` and resets the hasher. Returns the output size.
+    ///
+    /// Fails if `out` is shorter than the output size, leaving the hasher as is. Panics if the max length is exceeded.
+    fn finish_into(&mut self, out: &mut [u8]) -> Result<usize, BufferTooSmall>;
+    /// Returns the current hash state, leaving the hasher as is.
+    fn pause(&self) -> HashState;
+}
+
+impl<C: MdCompression> DynHasher for MdHasher<C>
+where
+    C::Output: AsRef<[u8]>,
+{
+    fn output_size(&self) -> usize {
+        size_of::<C::Output>()
+    }
+    fn update(&mut self, buf: &[u8]) -> usize {
+        StreamHasher::update(self, buf)
+    }
+    fn finish_into(&mut self, out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        let out = out.get_mut(..size_of::<C::Output>()).ok_or(BufferTooSmall)?;
+        out.copy_from_slice(core::mem::take(self).finish().as_ref());
+        Ok(out.len())
+    }
+    fn pause(&self) -> HashState {
+        Resumable::pause(self.clone())
+    }
+}
+
+/// A hasher whose algorithm is selected on runtime
+///
+/// Unlike `StreamHasher`, every method takes `&self` or `&mut self` and the output is written into a slice.
+#[derive(Clone)]
+pub enum AnyHasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl AnyHasher {
+    /// Create new instance of `algorithm` if the current policy allows it for every purpose.
+    ///
+    /// Use `with_purpose` for algorithms restricted to some purposes, e.g. SHA-1 for key identifiers.
+    pub fn new(algorithm: Algorithm) -> Result<Self, PolicyViolation> {
+        self_test::ensure();
+        policy::current().check_any(Some(algorithm))?;
+        Ok(Self::new_unchecked(algorithm))
+    }
+    /// Create new instance of `algorithm` without checking the policy
+    pub(crate) const fn new_unchecked(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Sha1 => AnyHasher::Sha1(Sha1::new()),
+            Algorithm::Sha256 => AnyHasher::Sha256(Sha256::new()),
+            Algorithm::Sha512 => AnyHasher::Sha512(Sha512::new()),
+        }
+    }
+    /// Recreate new instance from any hash state of `Sha1`, `Sha256` or `Sha512`.
+    ///
+    /// Like `Resumable::resume`, the policy is not checked; use `resume_with_purpose` for that.
+    pub fn resume(hash_state: HashState) -> Result<Self, hash_state::Error> {
+        match hash_state {
+            HashState::Sha1(_) => Sha1::resume(hash_state).map(AnyHasher::Sha1),
+            HashState::Sha256(_) => Sha256::resume(hash_state).map(AnyHasher::Sha256),
+            HashState::Sha512(_) => Sha512::resume(hash_state).map(AnyHasher::Sha512),
+            HashState::Md(_) | HashState::Multi(_) => Err(hash_state::Error::HashTypeNotMatch),
+        }
+    }
+    /// Returns the selected algorithm
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            AnyHasher::Sha1(_) => Algorithm::Sha1,
+            AnyHasher::Sha256(_) => Algorithm::Sha256,
+            AnyHasher::Sha512(_) => Algorithm::Sha512,
+        }
+    }
+    /// Output size in bytes
+    pub fn output_size(&self) -> usize {
+        self.algorithm().output_size()
+    }
+    /// Write to buffer. See `StreamHasher::update`.
+    pub fn update(&mut self, buf: &[u8]) -> usize {
+        match self {
+            AnyHasher::Sha1(hasher) => StreamHasher::update(hasher, buf),
+            AnyHasher::Sha256(hasher) => StreamHasher::update(hasher, buf),
+            AnyHasher::Sha512(hasher) => StreamHasher::update(hasher, buf),
+        }
+    }
+    /// Write the concatenation of `bufs`. See `StreamHasher::update_vectored`.
+    pub fn update_vectored<B: Deref<Target = [u8]>>(&mut self, bufs: &[B]) -> usize {
+        match self {
+            AnyHasher::Sha1(hasher) => hasher.update_vectored(bufs),
+            AnyHasher::Sha256(hasher) => hasher.update_vectored(bufs),
+            AnyHasher::Sha512(hasher) => hasher.update_vectored(bufs),
+        }
+    }
+    /// Write to buffer, refusing to exceed the max length. See `StreamHasher::try_update`.
+    pub fn try_update(&mut self, buf: &[u8]) -> Result<usize, LengthLimitExceeded> {
+        match self {
+            AnyHasher::Sha1(hasher) => hasher.try_update(buf),
+            AnyHasher::Sha256(hasher) => hasher.try_update(buf),
+            AnyHasher::Sha512(hasher) => hasher.try_update(buf),
+        }
+    }
+    /// Writes the final hash into `out` and resets the hasher. Returns the output size.
+    ///
+    /// Fails if `out` is shorter than the output size, leaving the hasher as is. Panics if the max length is exceeded.
+    pub fn finish_into(&mut self, out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        let out = out.get_mut(..self.output_size()).ok_or(BufferTooSmall)?;
+        match core::mem::replace(self, Self::new_unchecked(self.algorithm())) {
+            AnyHasher::Sha1(hasher) => out.copy_from_slice(&hasher.finish()),
+            AnyHasher::Sha256(hasher) => out.copy_from_slice(&hasher.finish()),
+            AnyHasher::Sha512(hasher) => out.copy_from_slice(&hasher.finish()),
+        }
+        Ok(out.len())
+    }
+    /// Returns the current hash state, leaving the hasher as is.
+    pub fn pause(&self) -> HashState {
+        match self.clone() {
+            AnyHasher::Sha1(hasher) => hasher.pause(),
+            AnyHasher::Sha256(hasher) => hasher.pause(),
+            AnyHasher::Sha512(hasher) => hasher.pause(),
+        }
+    }
+}
+
+impl DynHasher for AnyHasher {
+    fn output_size(&self) -> usize {
+        AnyHasher::output_size(self)
+    }
+    fn update(&mut self, buf: &[u8]) -> usize {
+        AnyHasher::update(self, buf)
+    }
+    fn finish_into(&mut self, out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        AnyHasher::finish_into(self, out)
+    }
+    fn pause(&self) -> HashState {
+        AnyHasher::pause(self)
+    }
+}
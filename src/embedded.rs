@@ -0,0 +1,118 @@
+//! `embedded-io` integration for `no_std` firmware
+//!
+//! Every hasher implements `embedded_io::Write`, and `embedded_io_async::Write` with the `embedded-io-async` feature.
+//! `HashingReader` tees an `embedded_io::Read` into a hasher, so a stream (e.g. an APDU exchange or a flash region)
+//! is hashed in the caller's buffer while it's consumed, without intermediate copies.
+//!
+//! Only available with the `embedded-io` feature.
+//!
+//! ```rust
+//! use embedded_io::Write;
+//! use streamsha::traits::StreamHasher;
+//! use streamsha::Sha256;
+//!
+//! let mut hasher = Sha256::new();
+//! hasher.write_all(b"abc").unwrap();
+//! assert_eq!(hasher.finish(), streamsha::sha256(b"abc"));
+//! ```
+use crate::dynamic::AnyHasher;
+use crate::hash_state::HashState;
+use crate::md::{MdCompression, MdHasher};
+use crate::traits::*;
+use core::convert::Infallible;
+use embedded_io::{ErrorType, Read, Write};
+
+impl<C: MdCompression> ErrorType for MdHasher<C> {
+    type Error = Infallible;
+}
+
+impl<C: MdCompression> Write for MdHasher<C> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        Ok(StreamHasher::update(self, buf))
+    }
+    fn flush(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl ErrorType for AnyHasher {
+    type Error = Infallible;
+}
+
+impl Write for AnyHasher {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        Ok(self.update(buf))
+    }
+    fn flush(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// A reader that hashes the data read from the inner reader
+pub struct HashingReader<R, H: StreamHasher> {
+    inner: R,
+    hasher: H,
+}
+
+impl<R, H: StreamHasher> HashingReader<R, H> {
+    /// Create new instance reading from `inner` into `hasher`
+    pub fn new(inner: R, hasher: H) -> Self {
+        Self { inner, hasher }
+    }
+    /// Returns the hasher
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+    /// Returns the hash state of the data read so far
+    pub fn pause(&self) -> HashState
+    where
+        H: Resumable + Clone,
+    {
+        self.hasher.clone().pause()
+    }
+    /// Unwraps the inner reader and the hasher
+    pub fn into_parts(self) -> (R, H) {
+        (self.inner, self.hasher)
+    }
+    /// Drops the inner reader and outputs the final hash of the data read so far.
+    pub fn finish(self) -> H::Output {
+        self.hasher.finish()
+    }
+}
+
+impl<R: ErrorType, H: StreamHasher> ErrorType for HashingReader<R, H> {
+    type Error = R::Error;
+}
+
+impl<R: Read, H: StreamHasher> Read for HashingReader<R, H> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, R::Error> {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+mod async_impl {
+    use super::*;
+
+    impl<C: MdCompression> embedded_io_async::Write for MdHasher<C> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            Ok(StreamHasher::update(self, buf))
+        }
+    }
+
+    impl embedded_io_async::Write for AnyHasher {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            Ok(self.update(buf))
+        }
+    }
+
+    impl<R: embedded_io_async::Read, H: StreamHasher> embedded_io_async::Read for HashingReader<R, H> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, R::Error> {
+            let len = self.inner.read(buf).await?;
+            self.hasher.update(&buf[..len]);
+            Ok(len)
+        }
+    }
+}
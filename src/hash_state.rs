@@ -1,9 +1,16 @@
 //! Hash state enum / state structs for each hash algorithms
+//!
+//! `HashState::encode` / `HashState::decode` convert a state to and from a stable binary encoding,
+//! so it can be persisted and resumed by another process or version of this crate.
 use crate::consts::*;
+use crate::output::BufferTooSmall;
+use crate::policy::PolicyViolation;
 pub enum HashState {
     Sha1(Sha1HashState),
     Sha256(Sha256HashState),
     Sha512(Sha512HashState),
+    Md(MdHashState),
+    Multi(MultiHashState),
 }
 pub struct Sha1HashState {
     pub h: [u32; 5],
@@ -23,7 +30,244 @@ pub struct Sha512HashState {
     pub block_len: usize,
     pub current_block: [u8; SHA512_BLOCK_SIZE],
 }
+/// Hash state of a generic `MdHasher`
+pub struct MdHashState {
+    /// `MdCompression::ID` of the compression function
+    pub id: u64,
+    /// Hash values, stored in the endianness of the compression function
+    pub h: [u8; MD_MAX_STATE_SIZE],
+    pub message_len: u128,
+    pub block_len: usize,
+    pub current_block: [u8; MD_MAX_BLOCK_SIZE],
+}
+/// Hash state of a `MultiHasher`, `None` for algorithms not in the set
+pub struct MultiHashState {
+    pub sha1: Option<Sha1HashState>,
+    pub sha256: Option<Sha256HashState>,
+    pub sha512: Option<Sha512HashState>,
+}
 #[derive(Debug)]
 pub enum Error{
-    HashTypeNotMatch
+    HashTypeNotMatch,
+    /// The bytes are not a valid encoded hash state
+    InvalidEncoding,
+    /// The pending block doesn't fit in a block, or doesn't match the message length
+    InvalidState,
+    /// The current policy forbids the hash
+    PolicyViolation(PolicyViolation),
+    /// The buffer is too small for the encoded state
+    BufferTooSmall,
+}
+
+impl From<BufferTooSmall> for Error {
+    fn from(_: BufferTooSmall) -> Self {
+        Error::BufferTooSmall
+    }
+}
+
+/// Magic bytes and version of the encoding
+const MAGIC: &[u8; 5] = b"SSHS\x01";
+const TAG_SHA1: u8 = 1;
+const TAG_SHA256: u8 = 2;
+const TAG_SHA512: u8 = 3;
+const TAG_MD: u8 = 4;
+const TAG_MULTI: u8 = 5;
+const SHA1_ENCODED_LEN: usize = 20 + 8 + 2 + SHA1_BLOCK_SIZE;
+const SHA256_ENCODED_LEN: usize = 32 + 8 + 2 + SHA256_BLOCK_SIZE;
+const SHA512_ENCODED_LEN: usize = 64 + 16 + 2 + SHA512_BLOCK_SIZE;
+const MD_ENCODED_LEN: usize = 8 + MD_MAX_STATE_SIZE + 16 + 2 + MD_MAX_BLOCK_SIZE;
+const MULTI_ENCODED_LEN: usize = 1 + SHA1_ENCODED_LEN + SHA256_ENCODED_LEN + SHA512_ENCODED_LEN;
+
+impl HashState {
+    /// The max length of an encoded hash state in bytes
+    pub const MAX_ENCODED_LEN: usize = MAGIC.len()
+        + 1
+        + if MD_ENCODED_LEN > MULTI_ENCODED_LEN { MD_ENCODED_LEN } else { MULTI_ENCODED_LEN };
+
+    /// Encodes the state into `buf` and returns the encoded length, at most `MAX_ENCODED_LEN`.
+    ///
+    /// The encoding is `SSHS`, version 1, a tag of the variant, and the fields in big endian.
+    /// Only the first `block_len` bytes of pending blocks are stored. Fails with `InvalidState` if a `block_len`
+    /// doesn't fit in its block, and with `BufferTooSmall` if `buf` is too short.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut w = Writer { buf, pos: 0 };
+        w.put(MAGIC)?;
+        match self {
+            HashState::Sha1(hs) => {
+                w.put(&[TAG_SHA1])?;
+                hs.encode(&mut w)?;
+            }
+            HashState::Sha256(hs) => {
+                w.put(&[TAG_SHA256])?;
+                hs.encode(&mut w)?;
+            }
+            HashState::Sha512(hs) => {
+                w.put(&[TAG_SHA512])?;
+                hs.encode(&mut w)?;
+            }
+            HashState::Md(hs) => {
+                w.put(&[TAG_MD])?;
+                w.put(&hs.id.to_be_bytes())?;
+                w.put(&hs.h)?;
+                w.put(&hs.message_len.to_be_bytes())?;
+                w.put_block(&hs.current_block, hs.block_len)?;
+            }
+            HashState::Multi(hs) => {
+                w.put(&[TAG_MULTI])?;
+                let flags = hs.sha1.is_some() as u8 | (hs.sha256.is_some() as u8) << 1 | (hs.sha512.is_some() as u8) << 2;
+                w.put(&[flags])?;
+                if let Some(hs) = &hs.sha1 {
+                    hs.encode(&mut w)?;
+                }
+                if let Some(hs) = &hs.sha256 {
+                    hs.encode(&mut w)?;
+                }
+                if let Some(hs) = &hs.sha512 {
+                    hs.encode(&mut w)?;
+                }
+            }
+        }
+        Ok(w.pos)
+    }
+    /// Decodes a state encoded by `encode`.
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader { bytes };
+        if r.take(MAGIC.len())? != MAGIC {
+            return Err(Error::InvalidEncoding);
+        }
+        let state = match r.byte()? {
+            TAG_SHA1 => HashState::Sha1(Sha1HashState::decode(&mut r)?),
+            TAG_SHA256 => HashState::Sha256(Sha256HashState::decode(&mut r)?),
+            TAG_SHA512 => HashState::Sha512(Sha512HashState::decode(&mut r)?),
+            TAG_MD => {
+                let id = r.u64()?;
+                let mut h = [0u8; MD_MAX_STATE_SIZE];
+                h.copy_from_slice(r.take(MD_MAX_STATE_SIZE)?);
+                let message_len = r.u128()?;
+                let mut current_block = [0u8; MD_MAX_BLOCK_SIZE];
+                let block_len = r.block(&mut current_block)?;
+                HashState::Md(MdHashState {
+                    id,
+                    h,
+                    message_len,
+                    block_len,
+                    current_block,
+                })
+            }
+            TAG_MULTI => {
+                let flags = r.byte()?;
+                if flags & !0b111 != 0 {
+                    return Err(Error::InvalidEncoding);
+                }
+                HashState::Multi(MultiHashState {
+                    sha1: if flags & 1 != 0 { Some(Sha1HashState::decode(&mut r)?) } else { None },
+                    sha256: if flags & 2 != 0 { Some(Sha256HashState::decode(&mut r)?) } else { None },
+                    sha512: if flags & 4 != 0 { Some(Sha512HashState::decode(&mut r)?) } else { None },
+                })
+            }
+            _ => return Err(Error::InvalidEncoding),
+        };
+        if !r.bytes.is_empty() {
+            return Err(Error::InvalidEncoding);
+        }
+        Ok(state)
+    }
+}
+
+macro_rules! impl_encoding {
+    ($state:ident, $word:ident, $len:ident, $block_size:expr) => {
+        impl $state {
+            fn encode(&self, w: &mut Writer) -> Result<(), Error> {
+                for word in &self.h {
+                    w.put(&word.to_be_bytes())?;
+                }
+                w.put(&self.message_len.to_be_bytes())?;
+                w.put_block(&self.current_block, self.block_len)
+            }
+            fn decode(r: &mut Reader) -> Result<Self, Error> {
+                let mut state = Self {
+                    h: Default::default(),
+                    message_len: 0,
+                    block_len: 0,
+                    current_block: [0; $block_size],
+                };
+                for word in state.h.iter_mut() {
+                    *word = r.$word()?;
+                }
+                state.message_len = r.$len()?;
+                state.block_len = r.block(&mut state.current_block)?;
+                Ok(state)
+            }
+        }
+    };
+}
+
+impl_encoding!(Sha1HashState, u32, u64, SHA1_BLOCK_SIZE);
+impl_encoding!(Sha256HashState, u32, u64, SHA256_BLOCK_SIZE);
+impl_encoding!(Sha512HashState, u64, u128, SHA512_BLOCK_SIZE);
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Result<(), BufferTooSmall> {
+        let area = self.buf.get_mut(self.pos..self.pos + bytes.len()).ok_or(BufferTooSmall)?;
+        area.copy_from_slice(bytes);
+        self.pos += bytes.len();
+        Ok(())
+    }
+    /// Writes the length of the pending block and its bytes, which must be shorter than `block` like `Reader::block` expects
+    fn put_block(&mut self, block: &[u8], block_len: usize) -> Result<(), Error> {
+        if block_len >= block.len() {
+            return Err(Error::InvalidState);
+        }
+        self.put(&(block_len as u16).to_be_bytes())?;
+        Ok(self.put(&block[..block_len])?)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::InvalidEncoding);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(bytes))
+    }
+    fn u64(&mut self) -> Result<u64, Error> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(bytes))
+    }
+    fn u128(&mut self) -> Result<u128, Error> {
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(self.take(16)?);
+        Ok(u128::from_be_bytes(bytes))
+    }
+    /// Reads a pending block into `block`, which must be longer than it
+    fn block(&mut self, block: &mut [u8]) -> Result<usize, Error> {
+        let mut len = [0u8; 2];
+        len.copy_from_slice(self.take(2)?);
+        let len = u16::from_be_bytes(len) as usize;
+        if len >= block.len() {
+            return Err(Error::InvalidEncoding);
+        }
+        block[..len].copy_from_slice(self.take(len)?);
+        Ok(len)
+    }
 }
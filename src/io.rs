@@ -0,0 +1,189 @@
+//! `std::io` integration
+//!
+//! Every hasher implements `io::Write`, so `io::copy` can write into it directly.
+//! `HashingReader` and `HashingWriter` hash data while passing it through.
+//!
+//! Only available with the `std` feature.
+//!
+//! ```rust
+//! use std::io;
+//! use streamsha::traits::StreamHasher;
+//! use streamsha::Sha256;
+//!
+//! let mut hasher = Sha256::new();
+//! io::copy(&mut &b"abc"[..], &mut hasher).unwrap();
+//! assert_eq!(hasher.finish(), streamsha::sha256(b"abc"));
+//! ```
+use crate::dynamic::AnyHasher;
+use crate::hash_state::HashState;
+use crate::md::{MdCompression, MdHasher};
+use crate::multi::MultiHasher;
+use crate::traits::*;
+use std::boxed::Box;
+use std::fmt;
+use std::io::{self, IoSlice, Read, Write};
+
+impl<C: MdCompression> Write for MdHasher<C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(StreamHasher::update(self, buf))
+    }
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        Ok(self.update_vectored(bufs))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Write for AnyHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(self.update(buf))
+    }
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        Ok(self.update_vectored(bufs))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Write for MultiHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(self.update(buf))
+    }
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        Ok(self.update_vectored(bufs))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A reader that hashes the data read from the inner reader
+pub struct HashingReader<R: Read, H: StreamHasher> {
+    inner: R,
+    hasher: H,
+}
+
+impl<R: Read, H: StreamHasher> HashingReader<R, H> {
+    /// Create new instance reading from `inner` into `hasher`
+    pub fn new(inner: R, hasher: H) -> Self {
+        Self { inner, hasher }
+    }
+    /// Returns the hasher
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+    /// Unwraps the inner reader and the hasher
+    pub fn into_parts(self) -> (R, H) {
+        (self.inner, self.hasher)
+    }
+    /// Drops the inner reader and outputs the final hash of the data read so far.
+    pub fn finish(self) -> H::Output {
+        self.hasher.finish()
+    }
+}
+
+impl<R: Read, H: StreamHasher> Read for HashingReader<R, H> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// A writer that hashes the data written to the inner writer
+pub struct HashingWriter<W: Write, H: StreamHasher> {
+    inner: W,
+    hasher: H,
+}
+
+impl<W: Write, H: StreamHasher> HashingWriter<W, H> {
+    /// Create new instance writing to `inner` and hashing into `hasher`
+    pub fn new(inner: W, hasher: H) -> Self {
+        Self { inner, hasher }
+    }
+    /// Returns the hasher
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+    /// Unwraps the inner writer and the hasher
+    pub fn into_parts(self) -> (W, H) {
+        (self.inner, self.hasher)
+    }
+    /// Drops the inner writer and outputs the final hash of the data written so far.
+    pub fn finish(self) -> H::Output {
+        self.hasher.finish()
+    }
+}
+
+impl<W: Write, H: StreamHasher> Write for HashingWriter<W, H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // only the bytes accepted by the inner writer
+        let len = self.inner.write(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reading failed in the middle of hashing. `state` holds the data read so far and can be resumed.
+pub struct ReadInterrupted {
+    pub error: io::Error,
+    pub state: Box<HashState>,
+}
+
+impl fmt::Debug for ReadInterrupted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadInterrupted").field("error", &self.error).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for ReadInterrupted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "hashing interrupted: {}", self.error)
+    }
+}
+
+impl std::error::Error for ReadInterrupted {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Hashes everything read from `reader` until EOF.
+///
+/// `io::ErrorKind::Interrupted` is retried. On other errors, the hash state of the data read so far is returned.
+pub fn hash_reader<H, R>(reader: R) -> Result<H::Output, ReadInterrupted>
+where
+    H: StreamHasher + Resumable + Default,
+    R: Read,
+{
+    hash_reader_with(H::default(), reader)
+}
+
+/// Continues hashing with `hasher`, e.g. resumed from `ReadInterrupted::state`, until EOF of `reader`.
+pub fn hash_reader_with<H, R>(mut hasher: H, mut reader: R) -> Result<H::Output, ReadInterrupted>
+where
+    H: StreamHasher + Resumable,
+    R: Read,
+{
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(hasher.finish()),
+            Ok(len) => {
+                hasher.update(&buf[..len]);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => {
+                return Err(ReadInterrupted {
+                    error,
+                    state: Box::new(hasher.pause()),
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Length-extension attacks, for security testing
+//!
+//! SHA-1 and SHA-2 (except truncated variants) output their whole internal state as the digest.
+//! Given `H(m)` and the length of `m`, anyone can therefore continue hashing from where `H(m)` stopped
+//! and compute `H(m || glue || suffix)` without knowing `m`, where `glue` is the padding `finish` appended to `m`.
+//!
+//! This is why a MAC built as `H(key || message)` is broken: an attacker who sees one valid tag can forge
+//! a valid tag for `message || glue || suffix` for any `suffix`, by guessing only the key length.
+//! Use HMAC instead.
+//!
+//! Only available with the `length-extension` feature.
+//!
+//! ```rust
+//! use streamsha::length_extension::LengthExtension;
+//! use streamsha::traits::StreamHasher;
+//! use streamsha::Sha256;
+//!
+//! // naive MAC: SHA-256(key || message)
+//! let key = b"secret";
+//! let message = b"user=guest";
+//! let mut hasher = Sha256::new();
+//! hasher.update(key);
+//! hasher.update(message);
+//! let tag = hasher.finish();
+//!
+//! // the attacker knows `message` and `tag`, and guesses the key length
+//! let forgery = Sha256::extend(&tag, (key.len() + message.len()) as u64, b"&admin=true");
+//!
+//! // the forged tag is valid for message || glue || suffix
+//! let mut hasher = Sha256::new();
+//! hasher.update(key);
+//! hasher.update(message);
+//! hasher.update(forgery.glue.as_bytes());
+//! hasher.update(b"&admin=true");
+//! assert_eq!(hasher.finish(), forgery.digest);
+//! ```
+use crate::consts::*;
+use crate::traits::StreamHasher;
+use crate::{Sha1, Sha256, Sha512};
+
+/// The padding `finish` appends to a message, i.e. 0x80||0x00...||size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GluePadding {
+    bytes: [u8; 2 * SHA512_BLOCK_SIZE],
+    len: usize,
+}
+
+impl GluePadding {
+    /// Returns the padding bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl AsRef<[u8]> for GluePadding {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// The result of a length-extension attack
+///
+/// `digest` is the digest of `original || glue || suffix`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Forgery<D> {
+    /// The padding to be placed between the original message and the suffix
+    pub glue: GluePadding,
+    /// The forged digest
+    pub digest: D,
+}
+
+/// Hashers whose state can be reconstructed from a final digest
+pub trait LengthExtension: StreamHasher + Sized {
+    /// Returns the padding `finish` appends to a message of `original_len` bytes.
+    fn glue_padding(original_len: u64) -> GluePadding;
+    /// Reconstructs the hasher that has hashed `original || glue`, from the digest of a message of `original_len` bytes.
+    ///
+    /// Panics if the length limit is exceeded.
+    fn from_digest(digest: &Self::Output, original_len: u64) -> Self;
+    /// Computes the digest of `original || glue || suffix` from the digest of the original message.
+    fn extend(digest: &Self::Output, original_len: u64, suffix: &[u8]) -> Forgery<Self::Output> {
+        let mut hasher = Self::from_digest(digest, original_len);
+        hasher.update(suffix);
+        Forgery {
+            glue: Self::glue_padding(original_len),
+            digest: hasher.finish(),
+        }
+    }
+}
+
+macro_rules! impl_length_extension {
+    ($hasher:ident, $raw:ty, $n:expr, $word_size:expr, $block_size:expr) => {
+        impl LengthExtension for $hasher {
+            fn glue_padding(original_len: u64) -> GluePadding {
+                let mut hasher = Self::new();
+                hasher.message_len = original_len.into();
+                hasher.block_len = (original_len % $block_size as u64) as usize;
+                let (blocks, blocks_len) = hasher.final_blocks();
+                let padded = &blocks[..blocks_len].as_flattened()[hasher.block_len..];
+                let mut glue = GluePadding {
+                    bytes: [0u8; 2 * SHA512_BLOCK_SIZE],
+                    len: padded.len(),
+                };
+                glue.bytes[..padded.len()].copy_from_slice(padded);
+                glue
+            }
+            fn from_digest(digest: &Self::Output, original_len: u64) -> Self {
+                let mut h = [0; $n];
+                for (word, bytes) in h.iter_mut().zip(digest.as_chunks::<$word_size>().0) {
+                    *word = <$raw>::from_be_bytes(*bytes);
+                }
+                let padded_len = original_len
+                    .checked_add(Self::glue_padding(original_len).len as u64)
+                    .expect("message length limit exceeded");
+                Self::with_iv(h, padded_len.into())
+            }
+        }
+    };
+}
+
+impl_length_extension!(Sha1, u32, 5, 4, SHA1_BLOCK_SIZE);
+impl_length_extension!(Sha256, u32, 8, 4, SHA256_BLOCK_SIZE);
+impl_length_extension!(Sha512, u64, 8, 8, SHA512_BLOCK_SIZE);
@@ -28,8 +28,70 @@
 //! assert_eq!(hash1, hash2);
 //! # }
 //! ```
+//!
+//! ## Features
+//! - `std`: Detects CPU features on runtime, and enables the `io`, `checkpoint` and `append` modules. Without it, accelerated backends are selected by `target_feature` on compile time.
+//! - `small`: Portable backend with rolled loops and 16 words of message schedule, for the smallest code.
+//! - `fast`: Portable backend with fully unrolled rounds. `small` takes precedence if both are enabled.
+//! - `tokio` / `futures-io`: The `async_io` module, with async hashing adapters for each runtime.
+//! - `embedded-io` / `embedded-io-async`: The `embedded` module, implementing the `embedded-io` traits for `no_std` firmware.
+//! - `digest`: Implements the RustCrypto `digest` traits for `Sha1`, `Sha256` and `Sha512`, and the `rustcrypto` module for `hmac::Hmac` and `pbkdf2`.
+//! - `self-test`: Runs `self_test` on the first construction of a hasher, and panics if it fails. The `const` constructors can't run it, so `update` and `finish` run it too.
+//! - `no-sha1-signatures` / `fips-approved`: The least strict `policy::Policy` that can be selected on runtime.
+//! - `length-extension`: The `length_extension` module, to demonstrate length-extension attacks on naive `H(key || message)` MACs.
+//!
+//! ## Minimum supported Rust version
+//! Rust 1.88, for slice chunking (`as_chunks`, `as_flattened`), `is_multiple_of`, inline `const` blocks
+//! and async functions in traits.
+//!
+//! ## Backends
+//! SHA-1 and SHA-256 use Intel SHA extensions on x86/x86-64 when available, and fall back to the portable implementation.
+//! SHA-512 vectorizes its message schedule when AVX2 is available. As `w[t]` depends on `w[t - 2]`, only two words
+//! are scheduled at once, with 128-bit operations; AVX2 byte-swaps the block and adds the round constants.
+//! `Sha256x4` / `Sha256x8` and `hash_many` hash independent messages in parallel lanes using SSE4.1 / AVX2, unless SHA extensions are available.
+//! All backends share the same `HashState`, so a state paused on one machine can be resumed on another.
+//! `HashState::encode` gives it a stable binary encoding, and `checkpoint::CheckpointingHasher` (with `std`)
+//! persists it periodically, so long-running hashes survive a restart. `append::AppendRehasher` keeps it in a sidecar
+//! of an append-only file, and hashes only the appended bytes on the next call.
+//!
+//! ## Digest output
+//! `finish_digest` returns a `Digest`, which prints as hex, parses from hex, base32, base64 and multibase,
+//! and compares in constant time.
+//!
+//! ## Verification
+//! `verify::Verifier` compares data with an expected digest in constant time, and `verify::VerifyingReader` (with `std`)
+//! fails reading at EOF if the digest doesn't match.
+//!
+//! ## Map hashing
+//! `StreamshaBuildHasher` implements `core::hash::BuildHasher` with hashes of bytes and integers stable across
+//! processes and machines, optionally keyed with HMAC.
+//!
+//! ## Runtime selection
+//! `Algorithm` maps names, OIDs and `HashState` variants to algorithms, and `AnyHasher` hashes with an algorithm selected on runtime.
+//! `MultiHasher` hashes with a set of algorithms in one pass.
+//! `AnyHasher::with_purpose` and `Sha1::with_purpose` etc. refuse algorithms forbidden by the `policy` for the purpose,
+//! and `AnyHasher::new` and `MultiHasher::new` refuse algorithms forbidden for any purpose.
+//!
+//! ## Merkle–Damgård engine
+//! `Sha1`, `Sha256` and `Sha512` are `md::MdHasher` instantiated with their compression functions.
+//! Other MD-style hashes get `StreamHasher` and `Resumable` by implementing `md::MdCompression`.
+//!
+//! ## Compression functions
+//! `compress_sha1`, `compress256` and `compress512` expose the raw compression functions.
+//! Together with `with_iv` and `finish_unpadded` on the hashers, they allow working with midstates directly.
+//!
+//! ## Const evaluation
+//! `sha1`, `sha224`, `sha256`, `sha384`, `sha512`, `sha512_224` and `sha512_256` are `const fn`,
+//! so digests of pinned data can be computed by the compiler:
+//! ```rust
+//! const DIGEST: [u8; 32] = streamsha::sha256(b"abc");
+//! assert_eq!(DIGEST[..4], [0xba, 0x78, 0x16, 0xbf]);
+//! ```
 
 #![no_std]
+#[cfg(feature = "std")]
+extern crate std;
+
 #[macro_use]
 mod utils;
 mod consts;
@@ -37,9 +99,48 @@ pub mod traits;
 mod sha1;
 mod sha256;
 mod sha512;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod sha_ni;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod simd;
+mod multi_lane;
+mod oneshot;
+mod compress;
+#[cfg(feature = "length-extension")]
+pub mod length_extension;
 pub mod hash_state;
+pub mod md;
+pub mod dynamic;
+pub mod multi;
+pub mod output;
+pub mod verify;
+pub mod self_test;
+pub mod policy;
+pub mod build_hasher;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "std")]
+pub mod checkpoint;
+#[cfg(feature = "std")]
+pub mod append;
+#[cfg(feature = "std")]
+mod sidecar;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+pub mod async_io;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
+#[cfg(feature = "digest")]
+pub mod rustcrypto;
 
 mod arith;
-pub use self::sha1::Sha1;
-pub use self::sha256::Sha256;
-pub use self::sha512::Sha512;
+pub use self::sha1::{Sha1, Sha1Compression};
+pub use self::sha256::{Sha256, Sha256Compression};
+pub use self::sha512::{Sha512, Sha512Compression};
+pub use self::multi_lane::{hash_many, Sha256x4, Sha256x8};
+pub use self::dynamic::{Algorithm, AnyHasher};
+pub use self::self_test::self_test;
+pub use self::multi::{MultiDigest, MultiHasher};
+pub use self::output::Digest;
+pub use self::build_hasher::StreamshaBuildHasher;
+pub use self::compress::{compress256, compress512, compress_sha1};
+pub use self::oneshot::{sha1, sha224, sha256, sha384, sha512, sha512_224, sha512_256};
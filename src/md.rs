@@ -0,0 +1,401 @@
+//! Generic Merkle–Damgård construction
+//!
+//! `MdHasher` implements streaming, padding and pausing on top of a compression function described by `MdCompression`.
+//! `Sha1`, `Sha256` and `Sha512` are built on it, and other MD-style hashes can be added the same way.
+//!
+//! ```rust
+//! use streamsha::md::{store_words, Endianness, MdCompression, MdHasher};
+//! use streamsha::traits::StreamHasher;
+//!
+//! /// A toy compression function
+//! struct XorCompression;
+//! impl MdCompression for XorCompression {
+//!     type Word = u32;
+//!     type State = [u32; 2];
+//!     type Block = [u8; 16];
+//!     type Output = [u8; 8];
+//!     const ID: u64 = 0x1234_5678_0000;
+//!     const IV: [u32; 2] = [0x01234567, 0x89abcdef];
+//!     const EMPTY_BLOCK: [u8; 16] = [0; 16];
+//!     const BLOCK_SIZE: usize = 16;
+//!     const LENGTH_SIZE: usize = 4;
+//!     const ENDIANNESS: Endianness = Endianness::Little;
+//!     const MAX_MESSAGE_LEN: u128 = u32::MAX as u128 / 8;
+//!     fn compress(state: &mut [u32; 2], blocks: &[u8]) {
+//!         for word in blocks.chunks(4) {
+//!             let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+//!             *state = [state[1], (state[0] ^ word).rotate_left(5)];
+//!         }
+//!     }
+//!     fn output(state: &[u32; 2]) -> [u8; 8] {
+//!         let mut output = [0; 8];
+//!         store_words(state, Self::ENDIANNESS, &mut output);
+//!         output
+//!     }
+//! }
+//!
+//! let mut hasher = MdHasher::<XorCompression>::new();
+//! hasher.update(b"abc");
+//! let digest = hasher.finish();
+//! ```
+use crate::consts::*;
+use crate::hash_state;
+use crate::hash_state::{HashState, MdHashState};
+use crate::output::{Digest, DigestBytes};
+use crate::self_test;
+use crate::traits::*;
+use core::cmp::min;
+use core::convert::TryInto;
+use core::ops::Deref;
+
+/// Byte order of words and of the length field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+/// Words of a chaining value
+pub trait MdWord: Copy {
+    /// The size of a word in bytes, at most 16
+    const SIZE: usize;
+    /// Writes the word into `out`, which is `SIZE` bytes long.
+    fn store(self, endianness: Endianness, out: &mut [u8]);
+    /// Reads a word from `bytes`, which is `SIZE` bytes long.
+    fn load(endianness: Endianness, bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_md_word {
+    ($t:ty) => {
+        impl MdWord for $t {
+            const SIZE: usize = core::mem::size_of::<$t>();
+            fn store(self, endianness: Endianness, out: &mut [u8]) {
+                let bytes = match endianness {
+                    Endianness::Big => self.to_be_bytes(),
+                    Endianness::Little => self.to_le_bytes(),
+                };
+                out.copy_from_slice(&bytes);
+            }
+            fn load(endianness: Endianness, bytes: &[u8]) -> Self {
+                let bytes = bytes.try_into().unwrap();
+                match endianness {
+                    Endianness::Big => <$t>::from_be_bytes(bytes),
+                    Endianness::Little => <$t>::from_le_bytes(bytes),
+                }
+            }
+        }
+    };
+}
+
+impl_md_word!(u32);
+impl_md_word!(u64);
+
+/// Writes `words` into `out`. The last word is truncated if `out` is not a multiple of the word size.
+pub fn store_words<W: MdWord>(words: &[W], endianness: Endianness, out: &mut [u8]) {
+    for (word, area) in words.iter().zip(out.chunks_mut(W::SIZE)) {
+        if area.len() == W::SIZE {
+            word.store(endianness, area);
+        } else {
+            let mut bytes = [0u8; 16];
+            word.store(endianness, &mut bytes[..W::SIZE]);
+            area.copy_from_slice(&bytes[..area.len()]);
+        }
+    }
+}
+
+/// Reads `words` from `bytes`
+pub fn load_words<W: MdWord>(bytes: &[u8], endianness: Endianness, words: &mut [W]) {
+    for (word, area) in words.iter_mut().zip(bytes.chunks(W::SIZE)) {
+        *word = W::load(endianness, area);
+    }
+}
+
+/// A compression function of a Merkle–Damgård hash
+///
+/// The message is padded with 0x80, zeros and the message length in bits, stored in `LENGTH_SIZE` bytes of `ENDIANNESS`.
+/// To be paused into `HashState::Md`, the chaining value must fit in `MD_MAX_STATE_SIZE` bytes
+/// and the block in `MD_MAX_BLOCK_SIZE` bytes.
+pub trait MdCompression {
+    /// Word type of the chaining value
+    type Word: MdWord;
+    /// Chaining value, e.g. `[u32; 8]`
+    type State: Copy + AsRef<[Self::Word]> + AsMut<[Self::Word]>;
+    /// A block, i.e. `[u8; BLOCK_SIZE]`
+    type Block: Copy + AsRef<[u8]> + AsMut<[u8]>;
+    /// Final hash
+    type Output;
+    /// Identifies the compression function in `MdHashState`. IDs below 0x10000 are reserved for this crate.
+    const ID: u64;
+    /// Initial hash values
+    const IV: Self::State;
+    /// A block filled with zeros
+    const EMPTY_BLOCK: Self::Block;
+    /// Block size in bytes
+    const BLOCK_SIZE: usize;
+    /// Size of the length field in bytes
+    const LENGTH_SIZE: usize;
+    /// Byte order of words and of the length field
+    const ENDIANNESS: Endianness;
+    /// The max length of message in bytes. The length in bits must fit in `u128`.
+    const MAX_MESSAGE_LEN: u128;
+    /// Compute hash for given blocks. `blocks` is a multiple of `BLOCK_SIZE` bytes.
+    fn compress(state: &mut Self::State, blocks: &[u8]);
+    /// Converts hash values to the final hash
+    fn output(state: &Self::State) -> Self::Output;
+    /// Wraps a paused state into `HashState`.
+    fn into_hash_state(state: MdHashState) -> HashState {
+        HashState::Md(state)
+    }
+    /// Unwraps a state to be resumed from `HashState`.
+    fn from_hash_state(hash_state: HashState) -> Result<MdHashState, hash_state::Error> {
+        match hash_state {
+            HashState::Md(hs) if hs.id == Self::ID => Ok(hs),
+            _ => Err(hash_state::Error::HashTypeNotMatch),
+        }
+    }
+}
+
+/// Calculates a Merkle–Damgård hash with the compression function `C`
+pub struct MdHasher<C: MdCompression> {
+    /// Hash values
+    pub(crate) h: C::State,
+    /// The length of message in bytes
+    pub(crate) message_len: u128,
+    /// The length of `current_block` in bytes
+    pub(crate) block_len: usize,
+    /// The incomplete block that is waiting to be filled and hashed
+    pub(crate) current_block: C::Block,
+}
+
+impl<C: MdCompression> MdHasher<C> {
+    /// Create new instance
+    pub const fn new() -> Self {
+        Self {
+            h: C::IV,
+            current_block: C::EMPTY_BLOCK,
+            block_len: 0usize,
+            message_len: 0u128,
+        }
+    }
+    /// Create an instance that continues from the chaining value `h`, after `message_len` bytes have been hashed.
+    ///
+    /// Panics if `message_len` is not a multiple of the block size or exceeds the max length.
+    pub fn with_iv(h: C::State, message_len: u128) -> Self {
+        self_test::ensure();
+        if !message_len.is_multiple_of(C::BLOCK_SIZE as u128) || message_len > C::MAX_MESSAGE_LEN {
+            panic!("invalid message length for a chaining value");
+        }
+        Self {
+            h,
+            current_block: C::EMPTY_BLOCK,
+            block_len: 0usize,
+            message_len,
+        }
+    }
+    /// Returns the raw chaining value without padding the message.
+    ///
+    /// Panics if the hashed message is not a multiple of the block size, i.e. there are pending bytes.
+    pub fn finish_unpadded(self) -> C::State {
+        self_test::ensure();
+        if self.block_len != 0 {
+            panic!("block is not filled");
+        }
+        self.h
+    }
+    /// Pad and process the last block, then output the final hash as `Digest`.
+    pub fn finish_digest(self) -> Digest<Self>
+    where
+        C::Output: DigestBytes,
+    {
+        Digest::new(self.finish())
+    }
+    /// Adds `len` to `message_len`.
+    ///
+    /// Once the max length is exceeded, `message_len` sticks to `u128::MAX` so that it never wraps back into range.
+    pub(crate) fn add_message_len(&mut self, len: usize) {
+        self.message_len = match self.message_len.checked_add(len as u128) {
+            Some(message_len) if message_len <= C::MAX_MESSAGE_LEN => message_len,
+            _ => u128::MAX,
+        };
+    }
+    /// Pads the pending block and returns the last blocks to be hashed, with the number of them.
+    pub(crate) fn final_blocks(&self) -> ([C::Block; 2], usize) {
+        if self.message_len > C::MAX_MESSAGE_LEN {
+            panic!("message length limit exceeded");
+        }
+        let mut blocks = [C::EMPTY_BLOCK; 2];
+        blocks[0].as_mut()[..self.block_len]
+            .copy_from_slice(&self.current_block.as_ref()[..self.block_len]);
+        blocks[0].as_mut()[self.block_len] = 0x80;
+        // data||0x80||size overflows block
+        let blocks_len = if self.block_len + 1 + C::LENGTH_SIZE > C::BLOCK_SIZE {
+            2
+        } else {
+            1
+        };
+        let len_field = &mut blocks[blocks_len - 1].as_mut()[C::BLOCK_SIZE - C::LENGTH_SIZE..];
+        let len_bits = self.message_len * 8;
+        let size = min(C::LENGTH_SIZE, 16);
+        match C::ENDIANNESS {
+            Endianness::Big => len_field[C::LENGTH_SIZE - size..]
+                .copy_from_slice(&len_bits.to_be_bytes()[16 - size..]),
+            Endianness::Little => {
+                len_field[..size].copy_from_slice(&len_bits.to_le_bytes()[..size])
+            }
+        }
+        (blocks, blocks_len)
+    }
+    /// `StreamHasher::update` without running the self-tests, for the self-tests themselves
+    pub(crate) fn update_unchecked(&mut self, buf: &[u8]) -> usize {
+        self.add_message_len(buf.len());
+        self.absorb(buf);
+        buf.len()
+    }
+    /// `StreamHasher::finish` without running the self-tests, for the self-tests themselves
+    pub(crate) fn finish_unchecked(self) -> C::Output {
+        let (blocks, blocks_len) = self.final_blocks();
+        let mut h = self.h;
+        for block in &blocks[..blocks_len] {
+            C::compress(&mut h, block.as_ref());
+        }
+        C::output(&h)
+    }
+    /// `Resumable::resume` without running the self-tests, for the self-tests themselves
+    pub(crate) fn resume_unchecked(hash_state: HashState) -> Result<Self, hash_state::Error> {
+        let hs = C::from_hash_state(hash_state)?;
+        // an overflowed `message_len` no longer tracks the pending bytes
+        if hs.block_len >= C::BLOCK_SIZE
+            || (hs.message_len <= C::MAX_MESSAGE_LEN
+                && hs.message_len % C::BLOCK_SIZE as u128 != hs.block_len as u128)
+        {
+            return Err(hash_state::Error::InvalidState);
+        }
+        let mut h = C::IV;
+        load_words(&hs.h, C::ENDIANNESS, h.as_mut());
+        let mut current_block = C::EMPTY_BLOCK;
+        current_block
+            .as_mut()
+            .copy_from_slice(&hs.current_block[..C::BLOCK_SIZE]);
+        Ok(Self {
+            h,
+            message_len: hs.message_len,
+            block_len: hs.block_len,
+            current_block,
+        })
+    }
+    /// Hashes `buf` without counting its length, compressing full blocks directly from `buf`.
+    fn absorb(&mut self, buf: &[u8]) {
+        let mut buf = buf;
+        if self.block_len != 0 {
+            // fill the pending block first
+            let writable_len = min(C::BLOCK_SIZE - self.block_len, buf.len());
+            let write_area =
+                &mut self.current_block.as_mut()[self.block_len..self.block_len + writable_len];
+            write_area.copy_from_slice(&buf[..writable_len]);
+            self.block_len += writable_len;
+            buf = &buf[writable_len..];
+            if self.block_len != C::BLOCK_SIZE {
+                // block is not filled yet
+                return;
+            }
+            self.process_block(); // perform hash calculation
+        }
+        // hash full blocks directly from buf, then keep the remaining bytes
+        let (blocks, remainder) = buf.split_at(buf.len() - buf.len() % C::BLOCK_SIZE);
+        if !blocks.is_empty() {
+            C::compress(&mut self.h, blocks);
+        }
+        self.current_block.as_mut()[..remainder.len()].copy_from_slice(remainder);
+        self.block_len = remainder.len();
+    }
+    /// Compute hash for current block
+    fn process_block(&mut self) {
+        if self.block_len != C::BLOCK_SIZE {
+            panic!("block is not filled");
+        }
+        C::compress(&mut self.h, self.current_block.as_ref());
+        #[cfg(not(all(feature = "fast", not(feature = "small"))))]
+        {
+            self.current_block = C::EMPTY_BLOCK; // next block
+        }
+        self.block_len = 0; // reset block
+    }
+}
+
+impl<C: MdCompression> StreamHasher for MdHasher<C> {
+    type Output = C::Output;
+    const BLOCK_SIZE: usize = C::BLOCK_SIZE;
+    fn update(&mut self, buf: &[u8]) -> usize {
+        self_test::ensure();
+        self.update_unchecked(buf)
+    }
+    fn update_vectored<B: Deref<Target = [u8]>>(&mut self, bufs: &[B]) -> usize {
+        self_test::ensure();
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        self.add_message_len(len);
+        for buf in bufs {
+            self.absorb(buf);
+        }
+        len
+    }
+    fn try_update(&mut self, buf: &[u8]) -> Result<usize, LengthLimitExceeded> {
+        match self.message_len.checked_add(buf.len() as u128) {
+            Some(message_len) if message_len <= C::MAX_MESSAGE_LEN => Ok(self.update(buf)),
+            _ => Err(LengthLimitExceeded),
+        }
+    }
+    fn finish(self) -> Self::Output {
+        self_test::ensure();
+        self.finish_unchecked()
+    }
+    fn try_finish(self) -> Result<Self::Output, LengthLimitExceeded> {
+        if self.message_len > C::MAX_MESSAGE_LEN {
+            return Err(LengthLimitExceeded);
+        }
+        Ok(self.finish())
+    }
+}
+
+impl<C: MdCompression> Resumable for MdHasher<C> {
+    fn pause(mut self) -> HashState {
+        // bytes after `block_len` may be left from previous blocks
+        self.current_block.as_mut()[self.block_len..].fill(0);
+        let mut h = [0u8; MD_MAX_STATE_SIZE];
+        store_words(
+            self.h.as_ref(),
+            C::ENDIANNESS,
+            &mut h[..self.h.as_ref().len() * C::Word::SIZE],
+        );
+        let mut current_block = [0u8; MD_MAX_BLOCK_SIZE];
+        current_block[..C::BLOCK_SIZE].copy_from_slice(self.current_block.as_ref());
+        C::into_hash_state(MdHashState {
+            id: C::ID,
+            h,
+            message_len: self.message_len,
+            block_len: self.block_len,
+            current_block,
+        })
+    }
+    fn resume(hash_state: HashState) -> Result<Self, hash_state::Error> {
+        self_test::ensure();
+        Self::resume_unchecked(hash_state)
+    }
+}
+
+impl<C: MdCompression> Clone for MdHasher<C> {
+    fn clone(&self) -> Self {
+        Self {
+            h: self.h,
+            message_len: self.message_len,
+            block_len: self.block_len,
+            current_block: self.current_block,
+        }
+    }
+}
+
+impl<C: MdCompression> Default for MdHasher<C> {
+    fn default() -> Self {
+        self_test::ensure();
+        Self::new()
+    }
+}
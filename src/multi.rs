@@ -0,0 +1,177 @@
+//! Hashing with several algorithms in one pass
+//!
+//! `MultiHasher` dispatches every update to a set of algorithms, e.g. for a SHA-1 key identifier,
+//! a SHA-256 fingerprint and a SHA-512 archive digest of the same certificate.
+//! It pauses into a single `HashState::Multi`, which resumes all algorithms at once.
+//!
+//! ```rust
+//! use streamsha::policy::Purpose;
+//! use streamsha::traits::StreamHasher;
+//! use streamsha::{Algorithm, MultiHasher};
+//!
+//! // SHA-1 is only allowed for key identifiers once a policy is selected
+//! let mut hasher = MultiHasher::with_purpose(&[Algorithm::Sha1, Algorithm::Sha256], Purpose::KeyIdentifier).unwrap();
+//! hasher.update(b"abc");
+//! let digests = hasher.finish();
+//! assert_eq!(digests.sha1, Some(streamsha::sha1(b"abc")));
+//! assert_eq!(digests.get(Algorithm::Sha256), Some(&streamsha::sha256(b"abc")[..]));
+//! assert_eq!(digests.sha512, None);
+//! ```
+use crate::dynamic::Algorithm;
+use crate::hash_state;
+use crate::hash_state::{HashState, MultiHashState};
+use crate::md::{MdCompression, MdHasher};
+use crate::policy::{self, PolicyViolation};
+use crate::self_test;
+use crate::traits::*;
+use crate::{Sha1, Sha256, Sha512};
+use core::ops::Deref;
+
+/// Hashes the same data with a set of algorithms
+#[derive(Clone)]
+pub struct MultiHasher {
+    sha1: Option<Sha1>,
+    sha256: Option<Sha256>,
+    sha512: Option<Sha512>,
+}
+
+/// Final hashes of `MultiHasher`, `None` for algorithms not in the set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultiDigest {
+    pub sha1: Option<[u8; 20]>,
+    pub sha256: Option<[u8; 32]>,
+    pub sha512: Option<[u8; 64]>,
+}
+
+impl MultiDigest {
+    /// Returns the hash of `algorithm`, or `None` if it was not in the set.
+    pub fn get(&self, algorithm: Algorithm) -> Option<&[u8]> {
+        match algorithm {
+            Algorithm::Sha1 => self.sha1.as_ref().map(|hash| &hash[..]),
+            Algorithm::Sha256 => self.sha256.as_ref().map(|hash| &hash[..]),
+            Algorithm::Sha512 => self.sha512.as_ref().map(|hash| &hash[..]),
+        }
+    }
+}
+
+impl MultiHasher {
+    /// Create new instance hashing with `algorithms` if the current policy allows each of them for every purpose.
+    /// Duplicates are ignored.
+    pub fn new(algorithms: &[Algorithm]) -> Result<Self, PolicyViolation> {
+        self_test::ensure();
+        let policy = policy::current();
+        algorithms.iter().try_for_each(|&algorithm| policy.check_any(Some(algorithm)))?;
+        Ok(Self::new_unchecked(algorithms))
+    }
+    /// `new` without checking the policy
+    pub(crate) fn new_unchecked(algorithms: &[Algorithm]) -> Self {
+        let contains = |algorithm| algorithms.contains(&algorithm);
+        Self {
+            sha1: if contains(Algorithm::Sha1) { Some(Sha1::new()) } else { None },
+            sha256: if contains(Algorithm::Sha256) { Some(Sha256::new()) } else { None },
+            sha512: if contains(Algorithm::Sha512) { Some(Sha512::new()) } else { None },
+        }
+    }
+    /// Returns whether `algorithm` is in the set
+    pub fn contains(&self, algorithm: Algorithm) -> bool {
+        match algorithm {
+            Algorithm::Sha1 => self.sha1.is_some(),
+            Algorithm::Sha256 => self.sha256.is_some(),
+            Algorithm::Sha512 => self.sha512.is_some(),
+        }
+    }
+    /// Returns the algorithms in the set
+    pub fn algorithms(&self) -> impl Iterator<Item = Algorithm> + '_ {
+        Algorithm::ALL.iter().copied().filter(move |&algorithm| self.contains(algorithm))
+    }
+}
+
+/// Whether `hasher` accepts `len` more bytes
+fn fits<C: MdCompression>(hasher: &Option<MdHasher<C>>, len: usize) -> bool {
+    match hasher {
+        Some(hasher) => matches!(hasher.message_len.checked_add(len as u128), Some(message_len) if message_len <= C::MAX_MESSAGE_LEN),
+        None => true,
+    }
+}
+
+impl StreamHasher for MultiHasher {
+    type Output = MultiDigest;
+    /// The largest block size, a multiple of all the others
+    const BLOCK_SIZE: usize = Sha512::BLOCK_SIZE;
+    fn update(&mut self, buf: &[u8]) -> usize {
+        if let Some(hasher) = &mut self.sha1 {
+            hasher.update(buf);
+        }
+        if let Some(hasher) = &mut self.sha256 {
+            hasher.update(buf);
+        }
+        if let Some(hasher) = &mut self.sha512 {
+            hasher.update(buf);
+        }
+        buf.len()
+    }
+    fn update_vectored<B: Deref<Target = [u8]>>(&mut self, bufs: &[B]) -> usize {
+        if let Some(hasher) = &mut self.sha1 {
+            hasher.update_vectored(bufs);
+        }
+        if let Some(hasher) = &mut self.sha256 {
+            hasher.update_vectored(bufs);
+        }
+        if let Some(hasher) = &mut self.sha512 {
+            hasher.update_vectored(bufs);
+        }
+        bufs.iter().map(|buf| buf.len()).sum()
+    }
+    /// Writes to all algorithms, or to none of them if any would exceed its max length.
+    fn try_update(&mut self, buf: &[u8]) -> Result<usize, LengthLimitExceeded> {
+        if fits(&self.sha1, buf.len()) && fits(&self.sha256, buf.len()) && fits(&self.sha512, buf.len()) {
+            Ok(self.update(buf))
+        } else {
+            Err(LengthLimitExceeded)
+        }
+    }
+    fn finish(self) -> MultiDigest {
+        MultiDigest {
+            sha1: self.sha1.map(StreamHasher::finish),
+            sha256: self.sha256.map(StreamHasher::finish),
+            sha512: self.sha512.map(StreamHasher::finish),
+        }
+    }
+    /// Fails if any algorithm has exceeded its max length.
+    fn try_finish(self) -> Result<MultiDigest, LengthLimitExceeded> {
+        if fits(&self.sha1, 0) && fits(&self.sha256, 0) && fits(&self.sha512, 0) {
+            Ok(self.finish())
+        } else {
+            Err(LengthLimitExceeded)
+        }
+    }
+}
+
+impl Resumable for MultiHasher {
+    fn pause(self) -> HashState {
+        HashState::Multi(MultiHashState {
+            sha1: self.sha1.map(|hasher| match hasher.pause() {
+                HashState::Sha1(hs) => hs,
+                _ => unreachable!(),
+            }),
+            sha256: self.sha256.map(|hasher| match hasher.pause() {
+                HashState::Sha256(hs) => hs,
+                _ => unreachable!(),
+            }),
+            sha512: self.sha512.map(|hasher| match hasher.pause() {
+                HashState::Sha512(hs) => hs,
+                _ => unreachable!(),
+            }),
+        })
+    }
+    fn resume(hash_state: HashState) -> Result<Self, hash_state::Error> {
+        match hash_state {
+            HashState::Multi(hs) => Ok(Self {
+                sha1: hs.sha1.map(|hs| Sha1::resume(HashState::Sha1(hs))).transpose()?,
+                sha256: hs.sha256.map(|hs| Sha256::resume(HashState::Sha256(hs))).transpose()?,
+                sha512: hs.sha512.map(|hs| Sha512::resume(HashState::Sha512(hs))).transpose()?,
+            }),
+            _ => Err(hash_state::Error::HashTypeNotMatch),
+        }
+    }
+}
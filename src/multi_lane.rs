@@ -0,0 +1,152 @@
+//! Multi-lane SHA-256 hashing independent messages in parallel
+use crate::consts::*;
+use crate::hash_state;
+use crate::hash_state::HashState;
+use crate::traits::*;
+use crate::md::MdCompression;
+use crate::self_test;
+use crate::{Sha256, Sha256Compression};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::{sha_ni, simd};
+use core::cmp::min;
+
+macro_rules! define_sha256_multi_lane {
+    ($name:ident, $lanes:literal, $available:path, $compress:path, $doc:literal) => {
+        #[doc = $doc]
+        ///
+        /// Each lane is an independent SHA-256 hasher, which can be paused into a normal `HashState::Sha256`.
+        pub struct $name {
+            lanes: [Sha256; $lanes],
+        }
+
+        impl $name {
+            /// Create new instance
+            pub const fn new() -> Self {
+                Self {
+                    lanes: [const { Sha256::new() }; $lanes],
+                }
+            }
+            /// Create new instance from independent hashers
+            pub fn from_lanes(lanes: [Sha256; $lanes]) -> Self {
+                self_test::ensure();
+                Self { lanes }
+            }
+            /// Split into independent hashers
+            pub fn into_lanes(self) -> [Sha256; $lanes] {
+                self.lanes
+            }
+            /// Write `bufs[i]` to the lane `i`. Returns the written bytes of each lane.
+            ///
+            /// Full blocks are hashed in parallel as long as every lane has them, so lanes should be fed with messages of similar length.
+            pub fn update(&mut self, bufs: [&[u8]; $lanes]) -> [usize; $lanes] {
+                self_test::ensure();
+                let written = bufs.map(|buf| buf.len());
+                let mut bufs = bufs;
+                for (lane, buf) in self.lanes.iter_mut().zip(bufs.iter_mut()) {
+                    if lane.block_len != 0 {
+                        // fill the pending block first
+                        let head_len = min(SHA256_BLOCK_SIZE - lane.block_len, buf.len());
+                        lane.update(&buf[..head_len]);
+                        *buf = &buf[head_len..];
+                    }
+                }
+                // every lane with remaining data has no pending block here
+                let lane_blocks = bufs.map(|buf| buf.as_chunks::<SHA256_BLOCK_SIZE>().0);
+                let blocks_len = lane_blocks.iter().map(|blocks| blocks.len()).min().unwrap_or(0);
+                if blocks_len > 0 {
+                    let mut states = self.lanes.each_ref().map(|lane| lane.h);
+                    for i in 0..blocks_len {
+                        Self::compress_lanes(&mut states, lane_blocks.map(|blocks| &blocks[i]));
+                    }
+                    for ((lane, state), buf) in self.lanes.iter_mut().zip(states).zip(bufs.iter_mut()) {
+                        lane.h = state;
+                        lane.add_message_len(blocks_len * SHA256_BLOCK_SIZE);
+                        *buf = &buf[blocks_len * SHA256_BLOCK_SIZE..];
+                    }
+                }
+                for (lane, buf) in self.lanes.iter_mut().zip(bufs) {
+                    lane.update(buf);
+                }
+                written
+            }
+            /// Pad and process the last pending block of each lane then output the final hashes.
+            pub fn finish(self) -> [[u8; 32]; $lanes] {
+                self_test::ensure();
+                let final_blocks = self.lanes.each_ref().map(Sha256::final_blocks);
+                let mut states = self.lanes.each_ref().map(|lane| lane.h);
+                Self::compress_lanes(&mut states, final_blocks.each_ref().map(|(blocks, _)| &blocks[0]));
+                for (state, (blocks, blocks_len)) in states.iter_mut().zip(final_blocks.iter()) {
+                    Sha256::compress_blocks(state, &blocks[1..*blocks_len]);
+                }
+                states.map(|state| Sha256Compression::output(&state))
+            }
+            /// Returns the current hash state of each lane.
+            pub fn pause(self) -> [HashState; $lanes] {
+                self.lanes.map(Resumable::pause)
+            }
+            /// Recreate new instance from hash states of each lane.
+            pub fn resume(hash_states: [HashState; $lanes]) -> Result<Self, hash_state::Error> {
+                let mut lanes = [const { Sha256::new() }; $lanes];
+                for (lane, hash_state) in lanes.iter_mut().zip(hash_states) {
+                    *lane = Sha256::resume(hash_state)?;
+                }
+                Ok(Self { lanes })
+            }
+            /// Compute hash for one block of each lane
+            fn compress_lanes(states: &mut [[u32; 8]; $lanes], blocks: [&[u8; SHA256_BLOCK_SIZE]; $lanes]) {
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                {
+                    // SHA extensions on a single lane outperform the parallel lanes
+                    if !sha_ni::available() && $available() {
+                        unsafe { $compress(states, blocks) };
+                        return;
+                    }
+                }
+                for (state, block) in states.iter_mut().zip(blocks) {
+                    Sha256::compress_blocks(state, core::slice::from_ref(block));
+                }
+            }
+        }
+        impl Default for $name {
+            fn default() -> Self {
+                self_test::ensure();
+                Self::new()
+            }
+        }
+    };
+}
+
+define_sha256_multi_lane!(
+    Sha256x4, 4, simd::sse41_available, simd::compress_sha256x4,
+    "Calculates SHA-256 of 4 independent messages, using SSE4.1 if available"
+);
+define_sha256_multi_lane!(
+    Sha256x8, 8, simd::avx2_available, simd::compress_sha256x8,
+    "Calculates SHA-256 of 8 independent messages, using AVX2 if available"
+);
+
+/// Calculates SHA-256 of each `inputs[i]` into `outputs[i]`, hashing up to 8 messages in parallel.
+///
+/// Panics if `inputs` and `outputs` have different lengths.
+pub fn hash_many(inputs: &[&[u8]], outputs: &mut [[u8; 32]]) {
+    assert_eq!(inputs.len(), outputs.len(), "inputs and outputs must have the same length");
+    let (inputs8, inputs) = inputs.as_chunks::<8>();
+    let (outputs8, outputs) = outputs.as_chunks_mut::<8>();
+    for (input, output) in inputs8.iter().zip(outputs8.iter_mut()) {
+        let mut hasher = Sha256x8::new();
+        hasher.update(*input);
+        *output = hasher.finish();
+    }
+    let (inputs4, inputs) = inputs.as_chunks::<4>();
+    let (outputs4, outputs) = outputs.as_chunks_mut::<4>();
+    for (input, output) in inputs4.iter().zip(outputs4.iter_mut()) {
+        let mut hasher = Sha256x4::new();
+        hasher.update(*input);
+        *output = hasher.finish();
+    }
+    for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+        let mut hasher = Sha256::new();
+        hasher.update(input);
+        *output = hasher.finish();
+    }
+}
@@ -0,0 +1,138 @@
+//! `const fn` one-shot hashing
+//!
+//! These functions can be evaluated in const contexts, e.g. to embed digests of pinned data as constants.
+//! They share the compression functions of the portable backend of the streamed hashers, but are much slower on runtime.
+//! Being `const`, they don't run the self-tests of the `self-test` feature.
+use crate::arith::{Word32, Word64};
+use crate::consts::*;
+use crate::{Sha1, Sha256, Sha512};
+
+/// Calculates SHA-1 of `data`
+pub const fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state = [0u32; 5];
+    let mut i = 0;
+    while i < 5 {
+        state[i] = SHA1_H[i].0;
+        i += 1;
+    }
+    let blocks_len = padded_len(data.len(), SHA1_BLOCK_SIZE, 8) / SHA1_BLOCK_SIZE;
+    let mut i = 0;
+    while i < blocks_len {
+        state = Sha1::const_compress(state, &padded_block(data, i * SHA1_BLOCK_SIZE, 8));
+        i += 1;
+    }
+    output32(&state)
+}
+
+/// Calculates SHA-224 of `data`
+pub const fn sha224(data: &[u8]) -> [u8; 28] {
+    output32(&sha256_state(&SHA224_H, data))
+}
+
+/// Calculates SHA-256 of `data`
+pub const fn sha256(data: &[u8]) -> [u8; 32] {
+    output32(&sha256_state(&SHA256_H, data))
+}
+
+/// Calculates SHA-384 of `data`
+pub const fn sha384(data: &[u8]) -> [u8; 48] {
+    output64(&sha512_state(&SHA384_H, data))
+}
+
+/// Calculates SHA-512 of `data`
+pub const fn sha512(data: &[u8]) -> [u8; 64] {
+    output64(&sha512_state(&SHA512_H, data))
+}
+
+/// Calculates SHA-512/224 of `data`
+pub const fn sha512_224(data: &[u8]) -> [u8; 28] {
+    output64(&sha512_state(&SHA512_224_H, data))
+}
+
+/// Calculates SHA-512/256 of `data`
+pub const fn sha512_256(data: &[u8]) -> [u8; 32] {
+    output64(&sha512_state(&SHA512_256_H, data))
+}
+
+/// Returns the length of the padded message, i.e. data||0x80||0x00...||size, in bytes.
+const fn padded_len(len: usize, block_size: usize, len_field_size: usize) -> usize {
+    (len + 1 + len_field_size).div_ceil(block_size) * block_size
+}
+
+/// Returns the block of the padded message starting from `offset`.
+const fn padded_block<const N: usize>(data: &[u8], offset: usize, len_field_size: usize) -> [u8; N] {
+    let len_field_offset = padded_len(data.len(), N, len_field_size) - len_field_size;
+    let len_bits = data.len() as u128 * 8;
+    let mut block = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        let pos = offset + i;
+        block[i] = if pos < data.len() {
+            data[pos]
+        } else if pos == data.len() {
+            0x80
+        } else if pos >= len_field_offset {
+            (len_bits >> (8 * (len_field_offset + len_field_size - 1 - pos))) as u8
+        } else {
+            0
+        };
+        i += 1;
+    }
+    block
+}
+
+/// Converts hash values to the final hash, truncated to `N` bytes.
+const fn output32<const N: usize>(h: &[u32]) -> [u8; N] {
+    let mut final_hash = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        final_hash[i] = (h[i / 4] >> (24 - 8 * (i % 4))) as u8;
+        i += 1;
+    }
+    final_hash
+}
+
+/// Converts hash values to the final hash, truncated to `N` bytes.
+const fn output64<const N: usize>(h: &[u64]) -> [u8; N] {
+    let mut final_hash = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        final_hash[i] = (h[i / 8] >> (56 - 8 * (i % 8))) as u8;
+        i += 1;
+    }
+    final_hash
+}
+
+/// Hashes the padded `data` from `iv` and returns the final hash values.
+const fn sha256_state(iv: &[Word32; 8], data: &[u8]) -> [u32; 8] {
+    let mut state = [0u32; 8];
+    let mut i = 0;
+    while i < 8 {
+        state[i] = iv[i].0;
+        i += 1;
+    }
+    let blocks_len = padded_len(data.len(), SHA256_BLOCK_SIZE, 8) / SHA256_BLOCK_SIZE;
+    let mut i = 0;
+    while i < blocks_len {
+        state = Sha256::const_compress(state, &padded_block(data, i * SHA256_BLOCK_SIZE, 8));
+        i += 1;
+    }
+    state
+}
+
+/// Hashes the padded `data` from `iv` and returns the final hash values.
+const fn sha512_state(iv: &[Word64; 8], data: &[u8]) -> [u64; 8] {
+    let mut state = [0u64; 8];
+    let mut i = 0;
+    while i < 8 {
+        state[i] = iv[i].0;
+        i += 1;
+    }
+    let blocks_len = padded_len(data.len(), SHA512_BLOCK_SIZE, 16) / SHA512_BLOCK_SIZE;
+    let mut i = 0;
+    while i < blocks_len {
+        state = Sha512::const_compress(state, &padded_block(data, i * SHA512_BLOCK_SIZE, 16));
+        i += 1;
+    }
+    state
+}
@@ -0,0 +1,398 @@
+//! Typed digest output and text encodings
+//!
+//! `Digest<A>` wraps the final hash of the hasher `A`, e.g. `Digest<Sha256>`.
+//! It prints as lowercase hex, parses from hex, and compares in constant time.
+//! The encoders write into caller-provided buffers, so they work without allocation.
+//!
+//! ```rust
+//! use streamsha::output::{Digest, Encoding};
+//! use streamsha::traits::StreamHasher;
+//! use streamsha::Sha256;
+//!
+//! const EXPECTED: Digest<Sha256> =
+//!     Digest::new(streamsha::output::hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+//!
+//! let mut hasher = Sha256::new();
+//! hasher.update(b"abc");
+//! let digest = hasher.finish_digest();
+//! assert_eq!(digest, EXPECTED);
+//!
+//! let mut buf = [0u8; 64];
+//! assert_eq!(digest.encode_into(Encoding::Base64, &mut buf).unwrap(), "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=");
+//! ```
+use crate::traits::StreamHasher;
+use core::fmt;
+use core::str::FromStr;
+
+/// Byte arrays that can be a digest
+pub trait DigestBytes: Copy + AsRef<[u8]> + AsMut<[u8]> {
+    /// An array filled with zeros
+    const ZERO: Self;
+}
+
+impl<const N: usize> DigestBytes for [u8; N] {
+    const ZERO: Self = [0u8; N];
+}
+
+/// Text encodings of digests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Lowercase hex. Uppercase is accepted on decoding.
+    Hex,
+    /// RFC 4648 base32 with padding. Lowercase is accepted on decoding.
+    Base32,
+    /// RFC 4648 base64 with padding
+    Base64,
+    /// RFC 4648 URL-safe base64 without padding
+    Base64Url,
+}
+
+impl Encoding {
+    /// Returns the length of encoded `len` bytes.
+    pub const fn encoded_len(self, len: usize) -> usize {
+        let (bits, group_bits, padded) = self.params();
+        if padded {
+            (len * 8).div_ceil(group_bits) * (group_bits / bits)
+        } else {
+            (len * 8).div_ceil(bits)
+        }
+    }
+    /// Returns the multibase prefix. Multibase uses the lowercase and unpadded variants of hex and base32, and unpadded base64.
+    pub const fn multibase_prefix(self) -> u8 {
+        match self {
+            Encoding::Hex => b'f',
+            Encoding::Base32 => b'b',
+            Encoding::Base64 => b'm',
+            Encoding::Base64Url => b'u',
+        }
+    }
+    /// Returns the encoding of a multibase prefix.
+    pub const fn from_multibase_prefix(prefix: u8) -> Option<Self> {
+        match prefix {
+            b'f' => Some(Encoding::Hex),
+            b'b' => Some(Encoding::Base32),
+            b'm' => Some(Encoding::Base64),
+            b'u' => Some(Encoding::Base64Url),
+            _ => None,
+        }
+    }
+    /// Bits per character, bits per padded group, and whether it's padded
+    const fn params(self) -> (usize, usize, bool) {
+        match self {
+            Encoding::Hex => (4, 8, false),
+            Encoding::Base32 => (5, 40, true),
+            Encoding::Base64 => (6, 24, true),
+            Encoding::Base64Url => (6, 24, false),
+        }
+    }
+    fn alphabet(self, multibase: bool) -> &'static [u8] {
+        match self {
+            Encoding::Hex => b"0123456789abcdef",
+            Encoding::Base32 if multibase => b"abcdefghijklmnopqrstuvwxyz234567",
+            Encoding::Base32 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567",
+            Encoding::Base64 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+            Encoding::Base64Url => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+        }
+    }
+    /// Returns the value of a character
+    fn value(self, c: u8) -> Option<u8> {
+        let c = match self {
+            Encoding::Hex => c.to_ascii_lowercase(),
+            Encoding::Base32 => c.to_ascii_uppercase(),
+            _ => c,
+        };
+        self.alphabet(false).iter().position(|&a| a == c).map(|v| v as u8)
+    }
+}
+
+/// The output buffer is too small
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferTooSmall;
+
+/// Errors on decoding a digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The decoded length doesn't match the digest size
+    InvalidLength,
+    /// A character is not in the alphabet, or the trailing bits are not zero
+    InvalidCharacter,
+    /// Unknown multibase prefix
+    UnknownPrefix,
+}
+
+/// Encodes `bytes` into `buf` and returns the encoded string.
+pub fn encode<'a>(encoding: Encoding, bytes: &[u8], buf: &'a mut [u8]) -> Result<&'a str, BufferTooSmall> {
+    encode_with(encoding, bytes, buf, false)
+}
+
+/// Encodes `bytes` into `buf` with a multibase prefix and returns the encoded string.
+pub fn encode_multibase<'a>(encoding: Encoding, bytes: &[u8], buf: &'a mut [u8]) -> Result<&'a str, BufferTooSmall> {
+    let (prefix, rest) = buf.split_first_mut().ok_or(BufferTooSmall)?;
+    *prefix = encoding.multibase_prefix();
+    let len = encode_with(encoding, bytes, rest, true)?.len();
+    Ok(core::str::from_utf8(&buf[..len + 1]).unwrap())
+}
+
+fn encode_with<'a>(encoding: Encoding, bytes: &[u8], buf: &'a mut [u8], multibase: bool) -> Result<&'a str, BufferTooSmall> {
+    let (bits, _, padded) = encoding.params();
+    let padded = padded && !multibase;
+    let len = if padded {
+        encoding.encoded_len(bytes.len())
+    } else {
+        (bytes.len() * 8).div_ceil(bits)
+    };
+    let out = buf.get_mut(..len).ok_or(BufferTooSmall)?;
+    let alphabet = encoding.alphabet(multibase);
+    let mask = (1u32 << bits) - 1;
+    let mut acc = 0u32;
+    let mut acc_bits = 0;
+    let mut i = 0;
+    for &byte in bytes {
+        acc = (acc << 8) | byte as u32;
+        acc_bits += 8;
+        while acc_bits >= bits {
+            acc_bits -= bits;
+            out[i] = alphabet[((acc >> acc_bits) & mask) as usize];
+            i += 1;
+        }
+    }
+    if acc_bits > 0 {
+        out[i] = alphabet[((acc << (bits - acc_bits)) & mask) as usize];
+        i += 1;
+    }
+    // pad to a whole group
+    while i < len {
+        out[i] = b'=';
+        i += 1;
+    }
+    Ok(core::str::from_utf8(out).unwrap())
+}
+
+/// Decodes `s` into `out`, whose length must match the decoded length.
+/// Padding is optional for the padded encodings, but must be complete if present.
+pub fn decode(encoding: Encoding, s: &str, out: &mut [u8]) -> Result<(), DecodeError> {
+    let (bits, _, padded) = encoding.params();
+    let len = (out.len() * 8).div_ceil(bits);
+    let s = s.as_bytes();
+    let s = match s.split_at_checked(len) {
+        Some((data, padding))
+            if padded && s.len() == encoding.encoded_len(out.len()) && padding.iter().all(|&c| c == b'=') =>
+        {
+            data
+        }
+        _ => s,
+    };
+    if s.len() != len {
+        return Err(DecodeError::InvalidLength);
+    }
+    let mut acc = 0u32;
+    let mut acc_bits = 0;
+    let mut i = 0;
+    for &c in s {
+        let value = encoding.value(c).ok_or(DecodeError::InvalidCharacter)?;
+        acc = (acc << bits) | value as u32;
+        acc_bits += bits;
+        if acc_bits >= 8 {
+            acc_bits -= 8;
+            out[i] = (acc >> acc_bits) as u8;
+            i += 1;
+        }
+    }
+    if acc & ((1 << acc_bits) - 1) != 0 {
+        // not canonical
+        return Err(DecodeError::InvalidCharacter);
+    }
+    Ok(())
+}
+
+/// Decodes a multibase string into `out`.
+pub fn decode_multibase(s: &str, out: &mut [u8]) -> Result<(), DecodeError> {
+    let prefix = *s.as_bytes().first().ok_or(DecodeError::InvalidLength)?;
+    let encoding = Encoding::from_multibase_prefix(prefix).ok_or(DecodeError::UnknownPrefix)?;
+    decode(encoding, &s[1..], out)
+}
+
+/// Parses hex in const context. Panics (fails compilation in const context) on invalid input.
+pub const fn hex<const N: usize>(s: &str) -> [u8; N] {
+    let s = s.as_bytes();
+    if s.len() != N * 2 {
+        panic!("invalid hex length");
+    }
+    let mut bytes = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        bytes[i] = (hex_value(s[i * 2]) << 4) | hex_value(s[i * 2 + 1]);
+        i += 1;
+    }
+    bytes
+}
+
+const fn hex_value(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => panic!("invalid hex character"),
+    }
+}
+
+/// Compares in constant time. Only the lengths may leak.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b) {
+        diff |= x ^ y;
+    }
+    core::hint::black_box(diff) == 0
+}
+
+/// The final hash of the hasher `A`
+pub struct Digest<A: StreamHasher>
+where
+    A::Output: DigestBytes,
+{
+    bytes: A::Output,
+}
+
+impl<A: StreamHasher> Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    /// Wraps a final hash
+    pub const fn new(bytes: A::Output) -> Self {
+        Self { bytes }
+    }
+    /// Returns the bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_ref()
+    }
+    /// Unwraps the final hash
+    pub fn into_inner(self) -> A::Output {
+        self.bytes
+    }
+    /// Encodes into `buf` and returns the encoded string.
+    pub fn encode_into<'a>(&self, encoding: Encoding, buf: &'a mut [u8]) -> Result<&'a str, BufferTooSmall> {
+        encode(encoding, self.as_bytes(), buf)
+    }
+    /// Encodes into `buf` with a multibase prefix and returns the encoded string.
+    pub fn encode_multibase_into<'a>(&self, encoding: Encoding, buf: &'a mut [u8]) -> Result<&'a str, BufferTooSmall> {
+        encode_multibase(encoding, self.as_bytes(), buf)
+    }
+    /// Decodes from `s`.
+    pub fn decode(encoding: Encoding, s: &str) -> Result<Self, DecodeError> {
+        let mut bytes = A::Output::ZERO;
+        decode(encoding, s, bytes.as_mut())?;
+        Ok(Self { bytes })
+    }
+    /// Decodes from a multibase string.
+    pub fn decode_multibase(s: &str) -> Result<Self, DecodeError> {
+        let mut bytes = A::Output::ZERO;
+        decode_multibase(s, bytes.as_mut())?;
+        Ok(Self { bytes })
+    }
+}
+
+impl<A: StreamHasher> Clone for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A: StreamHasher> Copy for Digest<A> where A::Output: DigestBytes {}
+
+impl<A: StreamHasher> PartialEq for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(self.as_bytes(), other.as_bytes())
+    }
+}
+
+impl<A: StreamHasher> Eq for Digest<A> where A::Output: DigestBytes {}
+
+impl<A: StreamHasher> PartialEq<[u8]> for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    fn eq(&self, other: &[u8]) -> bool {
+        ct_eq(self.as_bytes(), other)
+    }
+}
+
+impl<A: StreamHasher> AsRef<[u8]> for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<A: StreamHasher> fmt::LowerHex for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: StreamHasher> fmt::UpperHex for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: StreamHasher> fmt::Display for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl<A: StreamHasher> fmt::Debug for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Digest({:x})", self)
+    }
+}
+
+impl<A: StreamHasher> FromStr for Digest<A>
+where
+    A::Output: DigestBytes,
+{
+    type Err = DecodeError;
+    /// Parses hex
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::decode(Encoding::Hex, s)
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            DecodeError::InvalidLength => "invalid length",
+            DecodeError::InvalidCharacter => "invalid character",
+            DecodeError::UnknownPrefix => "unknown multibase prefix",
+        })
+    }
+}
@@ -0,0 +1,204 @@
+//! Approved-algorithm policy
+//!
+//! A `Policy` restricts which algorithms may be used for which `Purpose`. It's enforced by the purpose-aware
+//! constructors `with_purpose` and `resume_with_purpose` of `MdHasher` (e.g. `Sha1::with_purpose`), `AnyHasher`
+//! and `MultiHasher`, which return a `PolicyViolation` instead of a hasher.
+//!
+//! `AnyHasher::new` and `MultiHasher::new` take the algorithm from runtime data, so they're checked too.
+//! As their hashers may be used for any purpose, they require the algorithm to be allowed for every purpose,
+//! so SHA-1 needs `with_purpose` once a policy is selected. `StreamshaBuildHasher::new` and `with_key` check
+//! `Purpose::Integrity` and `Purpose::Mac`. `MdHasher::new` and `resume` (e.g. `Sha1::resume`) stay unchecked,
+//! as the hash is visible in the code, so checkpoints and readers of any hash keep working.
+//!
+//! The policy is set on runtime with `set`. The `no-sha1-signatures` and `fips-approved` features set the
+//! least strict policy `set` can select, so a build can't be loosened on runtime.
+//!
+//! ```rust
+//! use streamsha::policy::{self, Policy, Purpose};
+//! use streamsha::{Algorithm, AnyHasher, Sha1};
+//!
+//! policy::set(Policy::FipsApproved);
+//! assert!(AnyHasher::with_purpose(Algorithm::Sha1, Purpose::DigitalSignature).is_err());
+//! assert!(AnyHasher::new(Algorithm::from_oid("1.3.14.3.2.26").unwrap()).is_err());
+//! // legacy fingerprints such as the SubjectKeyIdentifier of a certificate are still allowed
+//! assert!(Sha1::with_purpose(Purpose::KeyIdentifier).is_ok());
+//! ```
+use crate::dynamic::{Algorithm, AnyHasher};
+use crate::hash_state;
+use crate::hash_state::HashState;
+use crate::md::{MdCompression, MdHasher};
+use crate::multi::MultiHasher;
+use crate::self_test;
+use crate::traits::Resumable;
+use crate::{Sha1Compression, Sha256Compression, Sha512Compression};
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Restrictions on algorithms, from the least to the most strict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Policy {
+    /// Every algorithm for every purpose
+    Unrestricted,
+    /// SHA-1 is forbidden for digital signatures
+    NoSha1Signatures,
+    /// Only SHA-2 for every purpose, and SHA-1 only for key identifiers and MACs. Custom `MdCompression`s are forbidden.
+    ///
+    /// HMAC-SHA-1 is still approved by NIST SP 800-131A, as HMAC doesn't depend on collision resistance.
+    FipsApproved,
+}
+
+/// What a hash is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Purpose {
+    /// Generating or verifying digital signatures
+    DigitalSignature,
+    /// Message authentication codes, e.g. HMAC
+    Mac,
+    /// Legacy fingerprints, e.g. the SubjectKeyIdentifier of a certificate
+    KeyIdentifier,
+    /// Checksums and content addressing
+    Integrity,
+}
+
+impl Purpose {
+    /// All purposes, from the most strictly restricted
+    pub const ALL: [Purpose; 4] = [Purpose::DigitalSignature, Purpose::Mac, Purpose::KeyIdentifier, Purpose::Integrity];
+}
+
+/// The algorithm is not allowed for the purpose under the current policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyViolation {
+    pub policy: Policy,
+    pub purpose: Purpose,
+    /// The forbidden algorithm, or `None` for a custom `MdCompression`
+    pub algorithm: Option<Algorithm>,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.algorithm {
+            Some(algorithm) => write!(f, "{} is not allowed", algorithm)?,
+            None => f.write_str("custom hash is not allowed")?,
+        }
+        write!(f, " for {:?} under {:?} policy", self.purpose, self.policy)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PolicyViolation {}
+
+impl Policy {
+    /// The least strict policy, selected by the `no-sha1-signatures` and `fips-approved` features
+    pub const FLOOR: Policy = if cfg!(feature = "fips-approved") {
+        Policy::FipsApproved
+    } else if cfg!(feature = "no-sha1-signatures") {
+        Policy::NoSha1Signatures
+    } else {
+        Policy::Unrestricted
+    };
+
+    /// Returns whether `algorithm` (`None` for a custom `MdCompression`) is allowed for `purpose`.
+    pub fn allows(self, algorithm: Option<Algorithm>, purpose: Purpose) -> bool {
+        match self {
+            Policy::Unrestricted => true,
+            Policy::NoSha1Signatures => !(algorithm == Some(Algorithm::Sha1) && purpose == Purpose::DigitalSignature),
+            Policy::FipsApproved => match algorithm {
+                Some(Algorithm::Sha1) => purpose == Purpose::KeyIdentifier || purpose == Purpose::Mac,
+                Some(_) => true,
+                None => false,
+            },
+        }
+    }
+    /// Checks `algorithm` against the policy
+    pub fn check(self, algorithm: Option<Algorithm>, purpose: Purpose) -> Result<(), PolicyViolation> {
+        if self.allows(algorithm, purpose) {
+            Ok(())
+        } else {
+            Err(PolicyViolation {
+                policy: self,
+                purpose,
+                algorithm,
+            })
+        }
+    }
+    /// Checks `algorithm` for every purpose, for hashers created without one
+    pub fn check_any(self, algorithm: Option<Algorithm>) -> Result<(), PolicyViolation> {
+        Purpose::ALL.iter().try_for_each(|&purpose| self.check(algorithm, purpose))
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(Policy::FLOOR as u8);
+
+/// Selects `policy`, or `Policy::FLOOR` if it's stricter.
+pub fn set(policy: Policy) {
+    CURRENT.store(policy.max(Policy::FLOOR) as u8, Ordering::Relaxed);
+}
+
+/// Returns the current policy
+pub fn current() -> Policy {
+    match CURRENT.load(Ordering::Relaxed) {
+        0 => Policy::Unrestricted,
+        1 => Policy::NoSha1Signatures,
+        _ => Policy::FipsApproved,
+    }
+}
+
+/// Returns the algorithm of `C`, or `None` for a custom `MdCompression`.
+pub(crate) fn algorithm_of<C: MdCompression>() -> Option<Algorithm> {
+    match C::ID {
+        Sha1Compression::ID => Some(Algorithm::Sha1),
+        Sha256Compression::ID => Some(Algorithm::Sha256),
+        Sha512Compression::ID => Some(Algorithm::Sha512),
+        _ => None,
+    }
+}
+
+impl<C: MdCompression> MdHasher<C> {
+    /// Create new instance if the current policy allows the hash for `purpose`.
+    pub fn with_purpose(purpose: Purpose) -> Result<Self, PolicyViolation> {
+        self_test::ensure();
+        current().check(algorithm_of::<C>(), purpose)?;
+        Ok(Self::new())
+    }
+    /// Recreate new instance from `hash_state` if the current policy allows the hash for `purpose`.
+    pub fn resume_with_purpose(hash_state: HashState, purpose: Purpose) -> Result<Self, hash_state::Error> {
+        let hasher = Self::resume(hash_state)?;
+        current().check(algorithm_of::<C>(), purpose).map_err(hash_state::Error::PolicyViolation)?;
+        Ok(hasher)
+    }
+}
+
+impl AnyHasher {
+    /// Create new instance of `algorithm` if the current policy allows it for `purpose`.
+    pub fn with_purpose(algorithm: Algorithm, purpose: Purpose) -> Result<Self, PolicyViolation> {
+        self_test::ensure();
+        current().check(Some(algorithm), purpose)?;
+        Ok(Self::new_unchecked(algorithm))
+    }
+    /// Recreate new instance from `hash_state` if the current policy allows its algorithm for `purpose`.
+    pub fn resume_with_purpose(hash_state: HashState, purpose: Purpose) -> Result<Self, hash_state::Error> {
+        let hasher = Self::resume(hash_state)?;
+        current().check(Some(hasher.algorithm()), purpose).map_err(hash_state::Error::PolicyViolation)?;
+        Ok(hasher)
+    }
+}
+
+impl MultiHasher {
+    /// Create new instance hashing with `algorithms` if the current policy allows each of them for `purpose`.
+    pub fn with_purpose(algorithms: &[Algorithm], purpose: Purpose) -> Result<Self, PolicyViolation> {
+        self_test::ensure();
+        let policy = current();
+        algorithms.iter().try_for_each(|&algorithm| policy.check(Some(algorithm), purpose))?;
+        Ok(Self::new_unchecked(algorithms))
+    }
+    /// Recreate new instance from `hash_state` if the current policy allows each of its algorithms for `purpose`.
+    pub fn resume_with_purpose(hash_state: HashState, purpose: Purpose) -> Result<Self, hash_state::Error> {
+        let hasher = Self::resume(hash_state)?;
+        let policy = current();
+        hasher
+            .algorithms()
+            .try_for_each(|algorithm| policy.check(Some(algorithm), purpose))
+            .map_err(hash_state::Error::PolicyViolation)?;
+        Ok(hasher)
+    }
+}
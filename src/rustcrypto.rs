@@ -0,0 +1,180 @@
+//! RustCrypto `digest` trait implementations
+//!
+//! `Sha1`, `Sha256` and `Sha512` implement `digest::Digest` (through its blanket impl), `FixedOutput`,
+//! `BlockSizeUser`, `Reset` and `AssociatedOid`, so they plug into crates generic over them, e.g. `hmac`, `rsa` and `ecdsa`.
+//! A hasher resumed from a `HashState` continues from the resumed state, while `Reset` starts over from the IV.
+//!
+//! `hmac::SimpleHmac` works with them directly. `hmac::Hmac` and `pbkdf2::pbkdf2_hmac` need a `CoreProxy`, which
+//! `digest` seals to its `CoreWrapper`, so this module wraps the block-level `DigestCore` into `rustcrypto::Sha1Digest`,
+//! `rustcrypto::Sha256Digest` and `rustcrypto::Sha512Digest` for them, named apart from the hashers so both can be
+//! glob-imported. These can't be paused; use the hashers for that.
+//!
+//! ```rust
+//! use hmac::{Hmac, Mac};
+//! use streamsha::rustcrypto;
+//!
+//! let mut mac = Hmac::<rustcrypto::Sha256Digest>::new_from_slice(b"key").unwrap();
+//! mac.update(b"message");
+//! let tag = mac.finalize().into_bytes();
+//! ```
+//!
+//! ```rust,compile_fail
+//! use hmac::{Hmac, Mac};
+//!
+//! // `streamsha::Sha256` is not a `CoreProxy`
+//! let mac = Hmac::<streamsha::Sha256>::new_from_slice(b"key");
+//! ```
+use crate::dynamic::Algorithm;
+use crate::md::{MdCompression, MdHasher};
+use crate::self_test;
+use crate::traits::StreamHasher;
+use crate::{Sha1Compression, Sha256Compression, Sha512Compression};
+use core::fmt;
+use digest::consts::{U128, U20, U32, U64};
+use digest::const_oid::{AssociatedOid, ObjectIdentifier};
+use digest::core_api::{
+    AlgorithmName, Block, BlockSizeUser, Buffer, BufferKindUser, CoreWrapper, FixedOutputCore, UpdateCore,
+};
+use digest::block_buffer::Eager;
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+
+/// Block-level core of `MdHasher<C>` for `digest::core_api`, i.e. the chaining value and the number of blocks.
+/// The pending bytes are buffered by the wrapper, e.g. `CoreWrapper` or `hmac::HmacCore`.
+pub struct DigestCore<C: MdCompression> {
+    h: C::State,
+    block_count: u128,
+}
+
+/// SHA-1 for crates that need a `CoreProxy`, e.g. `hmac::Hmac`
+pub type Sha1Digest = CoreWrapper<DigestCore<Sha1Compression>>;
+/// SHA-256 for crates that need a `CoreProxy`, e.g. `hmac::Hmac`
+pub type Sha256Digest = CoreWrapper<DigestCore<Sha256Compression>>;
+/// SHA-512 for crates that need a `CoreProxy`, e.g. `hmac::Hmac`
+pub type Sha512Digest = CoreWrapper<DigestCore<Sha512Compression>>;
+
+impl<C: MdCompression> Default for DigestCore<C> {
+    fn default() -> Self {
+        self_test::ensure();
+        Self {
+            h: C::IV,
+            block_count: 0,
+        }
+    }
+}
+
+impl<C: MdCompression> Clone for DigestCore<C> {
+    fn clone(&self) -> Self {
+        Self {
+            h: self.h,
+            block_count: self.block_count,
+        }
+    }
+}
+
+impl<C: MdCompression> Reset for DigestCore<C> {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<C: MdCompression> BufferKindUser for DigestCore<C>
+where
+    Self: BlockSizeUser,
+{
+    type BufferKind = Eager;
+}
+
+impl<C: MdCompression> UpdateCore for DigestCore<C>
+where
+    Self: BlockSizeUser,
+{
+    fn update_blocks(&mut self, blocks: &[Block<Self>]) {
+        self_test::ensure();
+        for block in blocks {
+            C::compress(&mut self.h, block);
+        }
+        self.block_count += blocks.len() as u128;
+    }
+}
+
+macro_rules! impl_digest {
+    ($compression:ty, $algorithm:expr, $output_size:ty, $block_size:ty) => {
+        impl HashMarker for MdHasher<$compression> {}
+
+        impl OutputSizeUser for MdHasher<$compression> {
+            type OutputSize = $output_size;
+        }
+
+        impl BlockSizeUser for MdHasher<$compression> {
+            type BlockSize = $block_size;
+        }
+
+        impl Update for MdHasher<$compression> {
+            fn update(&mut self, data: &[u8]) {
+                StreamHasher::update(self, data);
+            }
+        }
+
+        impl FixedOutput for MdHasher<$compression> {
+            fn finalize_into(self, out: &mut Output<Self>) {
+                out.copy_from_slice(&StreamHasher::finish(self));
+            }
+        }
+
+        impl Reset for MdHasher<$compression> {
+            fn reset(&mut self) {
+                *self = Self::new();
+            }
+        }
+
+        impl FixedOutputReset for MdHasher<$compression> {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                let hasher = core::mem::take(self);
+                out.copy_from_slice(&StreamHasher::finish(hasher));
+            }
+        }
+
+        impl AssociatedOid for MdHasher<$compression> {
+            const OID: ObjectIdentifier = ObjectIdentifier::new_unwrap($algorithm.oid());
+        }
+
+        impl HashMarker for DigestCore<$compression> {}
+
+        impl OutputSizeUser for DigestCore<$compression> {
+            type OutputSize = $output_size;
+        }
+
+        impl BlockSizeUser for DigestCore<$compression> {
+            type BlockSize = $block_size;
+        }
+
+        impl FixedOutputCore for DigestCore<$compression> {
+            fn finalize_fixed_core(&mut self, buffer: &mut Buffer<Self>, out: &mut Output<Self>) {
+                let message_len = self.block_count * <$compression as MdCompression>::BLOCK_SIZE as u128;
+                let mut hasher = MdHasher::<$compression>::with_iv(self.h, message_len);
+                StreamHasher::update(&mut hasher, buffer.get_data());
+                out.copy_from_slice(&StreamHasher::finish(hasher));
+            }
+        }
+
+        impl AssociatedOid for DigestCore<$compression> {
+            const OID: ObjectIdentifier = ObjectIdentifier::new_unwrap($algorithm.oid());
+        }
+
+        impl AlgorithmName for DigestCore<$compression> {
+            fn write_alg_name(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str($algorithm.name())
+            }
+        }
+
+        impl fmt::Debug for DigestCore<$compression> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "DigestCore<{}> {{ ... }}", $algorithm.name())
+            }
+        }
+    };
+}
+
+impl_digest!(Sha1Compression, Algorithm::Sha1, U20, U64);
+impl_digest!(Sha256Compression, Algorithm::Sha256, U32, U64);
+impl_digest!(Sha512Compression, Algorithm::Sha512, U64, U128);
@@ -0,0 +1,302 @@
+//! Power-on self-tests
+//!
+//! `self_test` runs known-answer tests (KATs) of every algorithm with the selected backend,
+//! and checks that hashers round-trip through a paused and encoded `HashState`.
+//! `Sha1::self_test`, `Sha256::self_test`, `Sha512::self_test` and `Algorithm::self_test` run them for one algorithm.
+//!
+//! With the `self-test` feature, the first construction of a hasher runs `self_test` and panics if it fails,
+//! and so does every construction after a failure. The `const` constructors, e.g. `Sha256::new`, can't run it,
+//! so `update` and `finish` run it too, as do the raw `compress_sha1` / `compress256` / `compress512` and `finish_unpadded`.
+//! The `const` one-shot functions like `sha256` can't run it either, and are exempt.
+//!
+//! ```rust
+//! streamsha::self_test().expect("self-test failed");
+//! ```
+use crate::dynamic::Algorithm;
+use crate::hash_state::HashState;
+use crate::md::{MdCompression, MdHasher};
+use crate::output;
+use crate::traits::*;
+use crate::{Sha1, Sha1Compression, Sha256, Sha256Compression, Sha512, Sha512Compression};
+use core::fmt;
+
+/// A failed self-test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestError {
+    /// The algorithm that failed
+    pub algorithm: Algorithm,
+    pub failure: Failure,
+}
+
+/// The kind of failed self-test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// The known-answer test at `index` gave a wrong hash
+    KnownAnswer { index: usize },
+    /// Pausing, encoding and resuming changed the hash
+    PauseResume,
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.failure {
+            Failure::KnownAnswer { index } => write!(f, "{} known-answer test #{} failed", self.algorithm, index),
+            Failure::PauseResume => write!(f, "{} pause/resume test failed", self.algorithm),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SelfTestError {}
+
+/// Runs the self-tests of all algorithms
+pub fn self_test() -> Result<(), SelfTestError> {
+    Algorithm::ALL.iter().try_for_each(|algorithm| algorithm.self_test())
+}
+
+impl Algorithm {
+    /// Runs the self-tests of the algorithm
+    pub fn self_test(self) -> Result<(), SelfTestError> {
+        match self {
+            Algorithm::Sha1 => run::<Sha1Compression>(self, SHA1_KATS),
+            Algorithm::Sha256 => run::<Sha256Compression>(self, SHA256_KATS),
+            Algorithm::Sha512 => run::<Sha512Compression>(self, SHA512_KATS),
+        }
+    }
+}
+
+macro_rules! impl_self_test {
+    ($hasher:ident, $algorithm:expr) => {
+        impl $hasher {
+            /// Runs the known-answer and pause/resume self-tests
+            pub fn self_test() -> Result<(), SelfTestError> {
+                $algorithm.self_test()
+            }
+        }
+    };
+}
+
+impl_self_test!(Sha1, Algorithm::Sha1);
+impl_self_test!(Sha256, Algorithm::Sha256);
+impl_self_test!(Sha512, Algorithm::Sha512);
+
+/// A message of a known-answer test
+enum Message {
+    Bytes(&'static [u8]),
+    /// A byte repeated `n` times
+    Repeat(u8, usize),
+}
+
+impl Message {
+    fn len(&self) -> usize {
+        match self {
+            Message::Bytes(bytes) => bytes.len(),
+            Message::Repeat(_, len) => *len,
+        }
+    }
+    /// Hashes the bytes from `start` to `end` into `hasher`
+    fn hash_into<C: MdCompression>(&self, hasher: &mut MdHasher<C>, start: usize, end: usize) {
+        match self {
+            Message::Bytes(bytes) => {
+                hasher.update_unchecked(&bytes[start..end]);
+            }
+            Message::Repeat(byte, _) => {
+                let chunk = [*byte; 64];
+                let mut pos = start;
+                while pos < end {
+                    let len = (end - pos).min(chunk.len());
+                    hasher.update_unchecked(&chunk[..len]);
+                    pos += len;
+                }
+            }
+        }
+    }
+}
+
+// the constructors, `update` and `finish` run the self-tests with the `self-test` feature, so the unchecked ones are used here.
+fn run<C: MdCompression>(algorithm: Algorithm, kats: &[(Message, C::Output)]) -> Result<(), SelfTestError>
+where
+    C::Output: AsRef<[u8]>,
+{
+    let fail = |failure| SelfTestError { algorithm, failure };
+    for (index, (message, expected)) in kats.iter().enumerate() {
+        let mut hasher = MdHasher::<C>::new();
+        message.hash_into(&mut hasher, 0, message.len());
+        if !output::ct_eq(hasher.finish_unchecked().as_ref(), expected.as_ref()) {
+            return Err(fail(Failure::KnownAnswer { index }));
+        }
+    }
+    // split the longest message around block boundaries
+    let (message, expected) = kats.iter().max_by_key(|(message, _)| message.len()).unwrap();
+    let len = message.len();
+    for split in [0, 1, C::BLOCK_SIZE - 1, C::BLOCK_SIZE, C::BLOCK_SIZE + 1, len].iter().copied() {
+        let split = split.min(len);
+        let mut hasher = MdHasher::<C>::new();
+        message.hash_into(&mut hasher, 0, split);
+        let mut encoded = [0u8; HashState::MAX_ENCODED_LEN];
+        let resumed = hasher
+            .pause()
+            .encode(&mut encoded)
+            .ok()
+            .and_then(|encoded_len| HashState::decode(&encoded[..encoded_len]).ok())
+            .and_then(|state| MdHasher::<C>::resume_unchecked(state).ok());
+        let mut hasher = match resumed {
+            Some(hasher) => hasher,
+            None => return Err(fail(Failure::PauseResume)),
+        };
+        message.hash_into(&mut hasher, split, len);
+        if !output::ct_eq(hasher.finish_unchecked().as_ref(), expected.as_ref()) {
+            return Err(fail(Failure::PauseResume));
+        }
+    }
+    Ok(())
+}
+
+/// Does nothing without the `self-test` feature
+#[cfg(not(feature = "self-test"))]
+#[inline(always)]
+pub(crate) fn ensure() {}
+
+/// Runs `self_test` on the first call, and panics if it fails.
+#[cfg(feature = "self-test")]
+pub(crate) fn ensure() {
+    static STATE: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(once::NOT_RUN);
+    once::ensure_with(&STATE, self_test);
+}
+
+/// The state machine of `ensure`
+#[cfg(any(feature = "self-test", test))]
+mod once {
+    use super::SelfTestError;
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    pub(super) const NOT_RUN: u8 = 0;
+    const RUNNING: u8 = 1;
+    pub(super) const PASSED: u8 = 2;
+    pub(super) const FAILED: u8 = 3;
+
+    /// Runs `test` on the first call with `state`, and panics if it failed or panicked.
+    pub(super) fn ensure_with(state: &AtomicU8, test: impl FnOnce() -> Result<(), SelfTestError>) {
+        // called on every `update`, so skip the read-modify-write once passed
+        if state.load(Ordering::Acquire) == PASSED {
+            return;
+        }
+        let mut test = Some(test);
+        loop {
+            match start(state) {
+                Ok(()) => {
+                    // a panicking test fails the self-tests, instead of leaving the other threads waiting
+                    let on_panic = StoreOnDrop(state, FAILED);
+                    let result = (test.take().unwrap())();
+                    core::mem::forget(on_panic);
+                    state.store(if result.is_ok() { PASSED } else { FAILED }, Ordering::Release);
+                    if let Err(e) = result {
+                        panic!("{}", e);
+                    }
+                    return;
+                }
+                Err(PASSED) => return,
+                Err(FAILED) => panic!("self-test failed"),
+                // another thread is running the self-tests
+                Err(_) => core::hint::spin_loop(),
+            }
+        }
+    }
+
+    /// Moves `state` from `NOT_RUN` to `RUNNING`, or returns the current state
+    #[cfg(target_has_atomic = "8")]
+    fn start(state: &AtomicU8) -> Result<(), u8> {
+        state
+            .compare_exchange(NOT_RUN, RUNNING, Ordering::Acquire, Ordering::Acquire)
+            .map(|_| ())
+    }
+
+    /// Moves `state` from `NOT_RUN` to `RUNNING`, or returns the current state.
+    ///
+    /// Without compare-and-swap, e.g. on thumbv6m, threads calling this at once may each run the self-tests.
+    #[cfg(not(target_has_atomic = "8"))]
+    fn start(state: &AtomicU8) -> Result<(), u8> {
+        match state.load(Ordering::Acquire) {
+            NOT_RUN => {
+                state.store(RUNNING, Ordering::Relaxed);
+                Ok(())
+            }
+            current => Err(current),
+        }
+    }
+
+    /// Stores a state when dropped, i.e. on unwinding
+    struct StoreOnDrop<'a>(&'a AtomicU8, u8);
+
+    impl Drop for StoreOnDrop<'_> {
+        fn drop(&mut self) {
+            self.0.store(self.1, Ordering::Release);
+        }
+    }
+}
+
+const SHA1_KATS: &[(Message, [u8; 20])] = &[
+    (Message::Bytes(b""), output::hex("da39a3ee5e6b4b0d3255bfef95601890afd80709")),
+    (Message::Bytes(b"abc"), output::hex("a9993e364706816aba3e25717850c26c9cd0d89d")),
+    (Message::Bytes(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"), output::hex("84983e441c3bd26ebaae4aa1f95129e5e54670f1")),
+    (Message::Repeat(0x61, 1000), output::hex("291e9a6c66994949b57ba5e650361e98fc36b1ba")),
+];
+
+const SHA256_KATS: &[(Message, [u8; 32])] = &[
+    (Message::Bytes(b""), output::hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")),
+    (Message::Bytes(b"abc"), output::hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")),
+    (Message::Bytes(&[0xbd]), output::hex("68325720aabd7c82f30f554b313d0570c95accbb7dc4b5aae11204c08ffe732b")),
+    (Message::Bytes(&[0xc9, 0x8c, 0x8e, 0x55]), output::hex("7abc22c0ae5af26ce93dbb94433a0e0b2e119d014f8e7f65bd56c61ccccd9504")),
+    (Message::Repeat(0x00, 55), output::hex("02779466cdec163811d078815c633f21901413081449002f24aa3e80f0b88ef7")),
+    (Message::Repeat(0x00, 56), output::hex("d4817aa5497628e7c77e6b606107042bbba3130888c5f47a375e6179be789fbb")),
+    (Message::Repeat(0x00, 57), output::hex("65a16cb7861335d5ace3c60718b5052e44660726da4cd13bb745381b235a1785")),
+    (Message::Repeat(0x00, 64), output::hex("f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b")),
+    (Message::Repeat(0x00, 1000), output::hex("541b3e9daa09b20bf85fa273e5cbd3e80185aa4ec298e765db87742b70138a53")),
+    (Message::Repeat(0x41, 1000), output::hex("c2e686823489ced2017f6059b8b239318b6364f6dcd835d0a519105a1eadd6e4")),
+    (Message::Repeat(0x55, 1005), output::hex("f4d62ddec0f3dd90ea1380fa16a5ff8dc4c54b21740650f24afc4120903552b0")),
+];
+
+const SHA512_KATS: &[(Message, [u8; 64])] = &[
+    (Message::Bytes(b""), output::hex("cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e")),
+    (Message::Bytes(b"abc"), output::hex("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f")),
+    (Message::Repeat(0x00, 111), output::hex("77ddd3a542e530fd047b8977c657ba6ce72f1492e360b2b2212cd264e75ec03882e4ff0525517ab4207d14c70c2259ba88d4d335ee0e7e20543d22102ab1788c")),
+    (Message::Repeat(0x00, 112), output::hex("2be2e788c8a8adeaa9c89a7f78904cacea6e39297d75e0573a73c756234534d6627ab4156b48a6657b29ab8beb73334040ad39ead81446bb09c70704ec707952")),
+    (Message::Repeat(0x00, 113), output::hex("0e67910bcf0f9ccde5464c63b9c850a12a759227d16b040d98986d54253f9f34322318e56b8feb86c5fb2270ed87f31252f7f68493ee759743909bd75e4bb544")),
+    (Message::Repeat(0x00, 122), output::hex("4f3f095d015be4a7a7cc0b8c04da4aa09e74351e3a97651f744c23716ebd9b3e822e5077a01baa5cc0ed45b9249e88ab343d4333539df21ed229da6f4a514e0f")),
+    (Message::Repeat(0x00, 1000), output::hex("ca3dff61bb23477aa6087b27508264a6f9126ee3a004f53cb8db942ed345f2f2d229b4b59c859220a1cf1913f34248e3803bab650e849a3d9a709edc09ae4a76")),
+    (Message::Repeat(0x41, 1000), output::hex("329c52ac62d1fe731151f2b895a00475445ef74f50b979c6f7bb7cae349328c1d4cb4f7261a0ab43f936a24b000651d4a824fcdd577f211aef8f806b16afe8af")),
+    (Message::Repeat(0x55, 1005), output::hex("59f5e54fe299c6a8764c6b199e44924a37f59e2b56c3ebad939b7289210dc8e4c21b9720165b0f4d4374c90f1bf4fb4a5ace17a1161798015052893a48c3d161")),
+];
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::once::*;
+    use super::*;
+    use core::sync::atomic::{AtomicU8, Ordering};
+    use std::panic::catch_unwind;
+
+    #[test]
+    fn it_fails_after_a_panicking_self_test() {
+        let state = AtomicU8::new(NOT_RUN);
+        assert!(catch_unwind(|| ensure_with(&state, || panic!("broken backend"))).is_err());
+        assert_eq!(state.load(Ordering::Acquire), FAILED);
+        // panics instead of waiting for the panicked run
+        assert!(catch_unwind(|| ensure_with(&state, || Ok(()))).is_err());
+    }
+
+    #[test]
+    fn it_runs_the_self_test_once() {
+        let state = AtomicU8::new(NOT_RUN);
+        ensure_with(&state, self_test);
+        ensure_with(&state, || unreachable!());
+        assert_eq!(state.load(Ordering::Acquire), PASSED);
+        let state = AtomicU8::new(NOT_RUN);
+        let error = SelfTestError {
+            algorithm: Algorithm::Sha1,
+            failure: Failure::PauseResume,
+        };
+        assert!(catch_unwind(|| ensure_with(&state, || Err(error))).is_err());
+        assert!(catch_unwind(|| ensure_with(&state, || Ok(()))).is_err());
+    }
+}
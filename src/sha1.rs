@@ -1,77 +1,230 @@
-use crate::arith::{rotl, Word32};
+use crate::arith::Word32;
+#[cfg(any(feature = "small", feature = "fast"))]
+use crate::arith::rotl;
 use crate::consts::*;
 use crate::hash_state;
-use crate::hash_state::HashState;
+use crate::hash_state::{HashState, MdHashState};
 use crate::traits::*;
-/// Calculates SHA-1
-pub struct Sha1 {
-    /// Hash values
-    h: [Word32; 5],
-    /// The max length of message (in bytes) defined in fips 180-4
-    message_len: u64,
-    /// The length of `current_block` in bytes
-    block_len: usize,
-    /// The incomplete block that is waiting to be filled and hashed
-    current_block: [u8; SHA1_BLOCK_SIZE],
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::sha_ni;
+use crate::md::{load_words, store_words, Endianness, MdCompression, MdHasher};
+
+/// A round of SHA-1, updating `e` and `b` in place. The caller rotates the variables instead of moving them.
+#[cfg(all(feature = "fast", not(feature = "small")))]
+macro_rules! sha1_round {
+    (load, $w:ident, $block:ident, $t:expr, $f:ident, $a:ident, $b:ident, $c:ident, $d:ident, $e:ident) => {
+        $w[$t & 15] = Self::get_word32_in_block($block, $t);
+        sha1_round!($w, $t, $f, $a, $b, $c, $d, $e);
+    };
+    (schedule, $w:ident, $block:ident, $t:expr, $f:ident, $a:ident, $b:ident, $c:ident, $d:ident, $e:ident) => {
+        $w[$t & 15] = rotl($w[($t + 13) & 15] ^ $w[($t + 8) & 15] ^ $w[($t + 2) & 15] ^ $w[$t & 15], 1);
+        sha1_round!($w, $t, $f, $a, $b, $c, $d, $e);
+    };
+    ($w:ident, $t:expr, $f:ident, $a:ident, $b:ident, $c:ident, $d:ident, $e:ident) => {
+        $e = rotl($a, 5) + Self::$f($b, $c, $d) + $e + SHA1_K($t) + $w[$t & 15];
+        $b = rotl($b, 30);
+    };
 }
+/// 5 rounds of SHA-1, after which the variables are back in place
+#[cfg(all(feature = "fast", not(feature = "small")))]
+macro_rules! sha1_rounds5 {
+    ($kind:ident, $w:ident, $block:ident, $t:expr, $f:ident, $a:ident, $b:ident, $c:ident, $d:ident, $e:ident) => {
+        sha1_round!($kind, $w, $block, $t, $f, $a, $b, $c, $d, $e);
+        sha1_round!($kind, $w, $block, $t + 1, $f, $e, $a, $b, $c, $d);
+        sha1_round!($kind, $w, $block, $t + 2, $f, $d, $e, $a, $b, $c);
+        sha1_round!($kind, $w, $block, $t + 3, $f, $c, $d, $e, $a, $b);
+        sha1_round!($kind, $w, $block, $t + 4, $f, $b, $c, $d, $e, $a);
+    };
+}
+/// SHA-1 compression function
+pub struct Sha1Compression;
+impl MdCompression for Sha1Compression {
+    type Word = u32;
+    type State = [u32; 5];
+    type Block = [u8; SHA1_BLOCK_SIZE];
+    type Output = [u8; 20];
+    const ID: u64 = 1;
+    const IV: [u32; 5] = Word32::unwrap_array(SHA1_H);
+    const EMPTY_BLOCK: [u8; SHA1_BLOCK_SIZE] = [0u8; SHA1_BLOCK_SIZE];
+    const BLOCK_SIZE: usize = SHA1_BLOCK_SIZE;
+    const LENGTH_SIZE: usize = 8;
+    const ENDIANNESS: Endianness = Endianness::Big;
+    const MAX_MESSAGE_LEN: u128 = SHA1_MAX_MESSAGE_LEN as u128;
+    fn compress(state: &mut [u32; 5], blocks: &[u8]) {
+        Sha1::compress_blocks(state, blocks.as_chunks().0);
+    }
+    fn output(state: &[u32; 5]) -> [u8; 20] {
+        let mut final_hash = [0u8; 20];
+        store_words(state, Self::ENDIANNESS, &mut final_hash);
+        final_hash
+    }
+    fn into_hash_state(state: MdHashState) -> HashState {
+        let mut h = [0; 5];
+        load_words(&state.h, Self::ENDIANNESS, &mut h);
+        HashState::Sha1(hash_state::Sha1HashState {
+            h,
+            message_len: state.message_len as u64,
+            block_len: state.block_len,
+            current_block: *state.current_block.first_chunk().unwrap(),
+        })
+    }
+    fn from_hash_state(hash_state: HashState) -> Result<MdHashState, hash_state::Error> {
+        match hash_state {
+            HashState::Sha1(hs) => {
+                let mut h = [0u8; MD_MAX_STATE_SIZE];
+                store_words(&hs.h, Self::ENDIANNESS, &mut h);
+                let mut current_block = [0u8; MD_MAX_BLOCK_SIZE];
+                current_block[..SHA1_BLOCK_SIZE].copy_from_slice(&hs.current_block);
+                Ok(MdHashState {
+                    id: Self::ID,
+                    h,
+                    message_len: hs.message_len.into(),
+                    block_len: hs.block_len,
+                    current_block,
+                })
+            }
+            _ => Err(hash_state::Error::HashTypeNotMatch),
+        }
+    }
+}
+/// Calculates SHA-1
+pub type Sha1 = MdHasher<Sha1Compression>;
 
 impl Sha1 {
-    /// Create new instance
-    pub const fn new() -> Self {
-        Self {
-            h: SHA1_H,
-            current_block: [0u8; SHA1_BLOCK_SIZE],
-            block_len: 0usize,
-            message_len: 0u64,
-        }
+    /// Write whole blocks at once.
+    ///
+    /// Blocks are hashed straight from `blocks` unless there are pending bytes in the current block.
+    pub fn update_blocks(&mut self, blocks: &[[u8; SHA1_BLOCK_SIZE]]) -> usize {
+        self.update(blocks.as_flattened())
     }
-    /// Compute hash for current block
-    fn process_block(&mut self) {
-        if self.block_len != SHA1_BLOCK_SIZE {
-            panic!("block is not filled");
+    /// Compute hash for given blocks, using SHA extensions if available
+    pub(crate) fn compress_blocks(state: &mut [u32; 5], blocks: &[[u8; SHA1_BLOCK_SIZE]]) {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if sha_ni::available() {
+                unsafe { sha_ni::compress_sha1(state, blocks) };
+                return;
+            }
+        }
+        let mut h = state.map(Word32);
+        for block in blocks {
+            Self::compress(&mut h, block);
         }
-        let mut w = [Word32(0); 80];
-        for t in 0..16 {
-            w[t] = self.get_word32_in_block(t);
+        *state = h.map(|w| w.0);
+    }
+    /// Compute hash for given block
+    #[cfg(not(any(feature = "small", feature = "fast")))]
+    pub(crate) fn compress(state: &mut [Word32; 5], block: &[u8; SHA1_BLOCK_SIZE]) {
+        *state = Self::const_compress(Word32::unwrap_array(*state), block).map(Word32);
+    }
+    /// Compute hash for given block, in const context. The portable backend and `oneshot` share it.
+    pub(crate) const fn const_compress(mut state: [u32; 5], block: &[u8; SHA1_BLOCK_SIZE]) -> [u32; 5] {
+        let mut w = [0u32; 80];
+        let mut t = 0;
+        while t < 16 {
+            w[t] = u32::from_be_bytes([block[t * 4], block[t * 4 + 1], block[t * 4 + 2], block[t * 4 + 3]]);
+            t += 1;
         }
-        for t in 16..80 {
-            w[t] = rotl(w[t-3]^w[t-8]^w[t-14]^w[t-16] , 1);
+        while t < 80 {
+            w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+            t += 1;
         }
-        let mut a = self.h[0];
-        let mut b = self.h[1];
-        let mut c = self.h[2];
-        let mut d = self.h[3];
-        let mut e = self.h[4];
-        for t in 0..80 {
-            let t1 = rotl(a, 5) + Self::ft(t, b, c, d) + e + SHA1_K(t) + w[t];
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        let mut t = 0;
+        while t < 80 {
+            let f = match t {
+                0..=19 => (b & c) ^ (!b & d),
+                40..=59 => (b & c) ^ (b & d) ^ (c & d),
+                _ => b ^ c ^ d,
+            };
+            let t1 = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(SHA1_K(t).0)
+                .wrapping_add(w[t]);
             e = d;
             d = c;
-            c = rotl(b, 30);
+            c = b.rotate_left(30);
             b = a;
             a = t1;
+            t += 1;
         }
-        self.h[0] = a + self.h[0];
-        self.h[1] = b + self.h[1];
-        self.h[2] = c + self.h[2];
-        self.h[3] = d + self.h[3];
-        self.h[4] = e + self.h[4];
-
-        self.current_block = [0u8; SHA1_BLOCK_SIZE]; // next block
-        self.block_len = 0; // reset block
+        let hash = [a, b, c, d, e];
+        let mut i = 0;
+        while i < 5 {
+            state[i] = state[i].wrapping_add(hash[i]);
+            i += 1;
+        }
+        state
+    }
+    /// Compute hash for given block, with a rolled loop and 16 words of message schedule
+    #[cfg(feature = "small")]
+    pub(crate) fn compress(state: &mut [Word32; 5], block: &[u8; SHA1_BLOCK_SIZE]) {
+        let mut w = [Word32(0); 16];
+        let mut hash = *state;
+        for t in 0..80 {
+            let wt = if t < 16 {
+                Self::get_word32_in_block(block, t)
+            } else {
+                rotl(w[(t + 13) & 15] ^ w[(t + 8) & 15] ^ w[(t + 2) & 15] ^ w[t & 15], 1)
+            };
+            w[t & 15] = wt;
+            let [a, b, c, d, e] = hash;
+            let t1 = rotl(a, 5) + Self::ft(t, b, c, d) + e + SHA1_K(t) + wt;
+            hash = [t1, a, rotl(b, 30), c, d];
+        }
+        for (word, x) in state.iter_mut().zip(hash) {
+            *word = x + *word;
+        }
+    }
+    /// Compute hash for given block, with fully unrolled rounds
+    #[cfg(all(feature = "fast", not(feature = "small")))]
+    pub(crate) fn compress(state: &mut [Word32; 5], block: &[u8; SHA1_BLOCK_SIZE]) {
+        let mut w = [Word32(0); 16];
+        let [mut a, mut b, mut c, mut d, mut e] = *state;
+        sha1_rounds5!(load, w, block, 0, ch, a, b, c, d, e);
+        sha1_rounds5!(load, w, block, 5, ch, a, b, c, d, e);
+        sha1_rounds5!(load, w, block, 10, ch, a, b, c, d, e);
+        sha1_round!(load, w, block, 15, ch, a, b, c, d, e);
+        sha1_round!(schedule, w, block, 16, ch, e, a, b, c, d);
+        sha1_round!(schedule, w, block, 17, ch, d, e, a, b, c);
+        sha1_round!(schedule, w, block, 18, ch, c, d, e, a, b);
+        sha1_round!(schedule, w, block, 19, ch, b, c, d, e, a);
+        sha1_rounds5!(schedule, w, block, 20, parity, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 25, parity, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 30, parity, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 35, parity, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 40, maj, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 45, maj, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 50, maj, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 55, maj, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 60, parity, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 65, parity, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 70, parity, a, b, c, d, e);
+        sha1_rounds5!(schedule, w, block, 75, parity, a, b, c, d, e);
+        state[0] = a + state[0];
+        state[1] = b + state[1];
+        state[2] = c + state[2];
+        state[3] = d + state[3];
+        state[4] = e + state[4];
     }
 
     /// Conbines 4 byte and returns as Word32.
-    const fn get_word32_in_block(&self, i: usize) -> Word32 {
-        let m: u32 = ((self.current_block[i * 4] as u32) << 24)
-            + ((self.current_block[i * 4 + 1] as u32) << 16)
-            + ((self.current_block[i * 4 + 2] as u32) << 8)
-            + (self.current_block[i * 4 + 3] as u32);
+    #[cfg(any(feature = "small", feature = "fast"))]
+    const fn get_word32_in_block(block: &[u8; SHA1_BLOCK_SIZE], i: usize) -> Word32 {
+        let m: u32 = ((block[i * 4] as u32) << 24)
+            + ((block[i * 4 + 1] as u32) << 16)
+            + ((block[i * 4 + 2] as u32) << 8)
+            + (block[i * 4 + 3] as u32);
         Word32(m)
     }
 }
 
 /// SHA1 functions
+#[cfg(any(feature = "small", feature = "fast"))]
 impl Sha1 {
+    #[cfg_attr(all(feature = "fast", not(feature = "small")), allow(dead_code))]
     fn ft(t: usize, x: Word32, y: Word32, z: Word32) -> Word32 {
         match t {
             0..=19 => Self::ch(x, y, z),
@@ -91,84 +244,3 @@ impl Sha1 {
         x ^ y ^ z
     }
 }
-impl StreamHasher for Sha1 {
-    type Output = [u8; 20];
-    const BLOCK_SIZE: usize = SHA1_BLOCK_SIZE;
-    fn update(&mut self, buf: &[u8]) -> usize {
-        let len = buf.len();
-        if len == 0 {
-            // if no data or no remaining data, stop
-            return 0;
-        }
-        let writable_len = Self::BLOCK_SIZE - self.block_len;
-        let writable_area = &mut self.current_block[self.block_len..];
-
-        if len >= writable_len {
-            // overflows block or buf.len() == writable_len
-            writable_area.clone_from_slice(&buf[0..writable_len]); // fill block
-            self.block_len += writable_len;
-            self.message_len += writable_len as u64;
-            self.process_block(); // perform hash calculation
-            self.update(&buf[writable_len..]); // recursively write remaining
-        } else {
-            // don't fill block
-            let write_area = &mut self.current_block[self.block_len..self.block_len + len];
-            write_area.clone_from_slice(&buf[..]);
-            self.block_len += len;
-            self.message_len += len as u64;
-        }
-        len
-    }
-    fn finish(mut self) -> Self::Output {
-        self.current_block[self.block_len] = 0x80;
-        if self.block_len + 1 + 8 > Self::BLOCK_SIZE {
-            // data||0x80||size(u64) overflows block
-            self.block_len = Self::BLOCK_SIZE;
-            self.process_block(); // perform hash calculation
-        }
-        let writable_area = &mut self.current_block[Self::BLOCK_SIZE - 8..Self::BLOCK_SIZE];
-        let len_bits = self.message_len * 8;
-        writable_area.clone_from_slice(&len_bits.to_be_bytes());
-        self.block_len = Self::BLOCK_SIZE;
-        self.process_block();
-        let mut final_hash: Self::Output = Default::default();
-        for i in 0..5 {
-            let word_area = &mut final_hash[i * 4..i * 4 + 4];
-            word_area.clone_from_slice(&self.h[i].0.to_be_bytes());
-        }
-        return final_hash;
-    }
-}
-impl Resumable for Sha1 {
-    fn pause(self) -> HashState {
-        let h: [u32; 5] = [
-            self.h[0].0,
-            self.h[1].0,
-            self.h[2].0,
-            self.h[3].0,
-            self.h[4].0,
-        ];
-        HashState::Sha1(hash_state::Sha1HashState {
-            h,
-            message_len: self.message_len,
-            block_len: self.block_len,
-            current_block: self.current_block,
-        })
-    }
-    fn resume(hash_state: HashState) -> Result<Self, hash_state::Error> {
-        match hash_state {
-            HashState::Sha1(hs) => Ok(Self {
-                h: arr32![hs.h[0], hs.h[1], hs.h[2], hs.h[3], hs.h[4]],
-                message_len: hs.message_len,
-                block_len: hs.block_len,
-                current_block: hs.current_block,
-            }),
-            _ => Err(hash_state::Error::HashTypeNotMatch),
-        }
-    }
-}
-impl Default for Sha1 {
-    fn default() -> Self {
-        Self::new()
-    }
-}
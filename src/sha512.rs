@@ -1,93 +1,245 @@
-use crate::arith::{Word64, rotr};
+use crate::arith::Word64;
+#[cfg(any(feature = "small", feature = "fast"))]
+use crate::arith::rotr;
+use crate::consts::*;
 use crate::hash_state;
-use crate::hash_state::HashState;
+use crate::hash_state::{HashState, MdHashState};
 use crate::traits::*;
-use crate::consts::*;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::simd;
+use crate::md::{load_words, store_words, Endianness, MdCompression, MdHasher};
 
-/// Calculates SHA-512
-pub struct Sha512 {
-    /// Hash values
-    h: [Word64; 8],
-    /// The max length of message (in bytes) defined in fips 180-4
-    message_len: u128,
-    /// The length of `current_block` in bytes
-    block_len: usize,
-    /// The incomplete block that is waiting to be filled and hashed
-    current_block: [u8; SHA512_BLOCK_SIZE],
+/// A round of SHA-512, updating `d` and `h` in place. The caller rotates the variables instead of moving them.
+#[cfg(all(feature = "fast", not(feature = "small")))]
+macro_rules! sha512_round {
+    (load, $w:ident, $block:ident, $t:expr, $a:ident, $b:ident, $c:ident, $d:ident, $e:ident, $f:ident, $g:ident, $h:ident) => {
+        $w[$t & 15] = Self::get_word64_in_block($block, $t);
+        sha512_round!($w, $t, $a, $b, $c, $d, $e, $f, $g, $h);
+    };
+    (schedule, $w:ident, $block:ident, $t:expr, $a:ident, $b:ident, $c:ident, $d:ident, $e:ident, $f:ident, $g:ident, $h:ident) => {
+        $w[$t & 15] = Self::lsigma1($w[($t + 14) & 15]) + $w[($t + 9) & 15] + Self::lsigma0($w[($t + 1) & 15]) + $w[$t & 15];
+        sha512_round!($w, $t, $a, $b, $c, $d, $e, $f, $g, $h);
+    };
+    ($w:ident, $t:expr, $a:ident, $b:ident, $c:ident, $d:ident, $e:ident, $f:ident, $g:ident, $h:ident) => {
+        let t1 = $h + Self::sigma1($e) + Self::ch($e, $f, $g) + SHA512_K[$t] + $w[$t & 15];
+        $d = $d + t1;
+        $h = t1 + Self::sigma0($a) + Self::maj($a, $b, $c);
+    };
+}
+/// 8 rounds of SHA-512, after which the variables are back in place
+#[cfg(all(feature = "fast", not(feature = "small")))]
+macro_rules! sha512_rounds8 {
+    ($kind:ident, $w:ident, $block:ident, $t:expr, $a:ident, $b:ident, $c:ident, $d:ident, $e:ident, $f:ident, $g:ident, $h:ident) => {
+        sha512_round!($kind, $w, $block, $t, $a, $b, $c, $d, $e, $f, $g, $h);
+        sha512_round!($kind, $w, $block, $t + 1, $h, $a, $b, $c, $d, $e, $f, $g);
+        sha512_round!($kind, $w, $block, $t + 2, $g, $h, $a, $b, $c, $d, $e, $f);
+        sha512_round!($kind, $w, $block, $t + 3, $f, $g, $h, $a, $b, $c, $d, $e);
+        sha512_round!($kind, $w, $block, $t + 4, $e, $f, $g, $h, $a, $b, $c, $d);
+        sha512_round!($kind, $w, $block, $t + 5, $d, $e, $f, $g, $h, $a, $b, $c);
+        sha512_round!($kind, $w, $block, $t + 6, $c, $d, $e, $f, $g, $h, $a, $b);
+        sha512_round!($kind, $w, $block, $t + 7, $b, $c, $d, $e, $f, $g, $h, $a);
+    };
 }
 
-impl Sha512 {
-    /// Create new instance
-    pub fn new() -> Self {
-        Self {
-            h: SHA512_H,
-            current_block: [0u8; SHA512_BLOCK_SIZE],
-            block_len: 0usize,
-            message_len: 0u128,
+/// SHA-512 compression function
+pub struct Sha512Compression;
+impl MdCompression for Sha512Compression {
+    type Word = u64;
+    type State = [u64; 8];
+    type Block = [u8; SHA512_BLOCK_SIZE];
+    type Output = [u8; 64];
+    const ID: u64 = 512;
+    const IV: [u64; 8] = Word64::unwrap_array(SHA512_H);
+    const EMPTY_BLOCK: [u8; SHA512_BLOCK_SIZE] = [0u8; SHA512_BLOCK_SIZE];
+    const BLOCK_SIZE: usize = SHA512_BLOCK_SIZE;
+    const LENGTH_SIZE: usize = 16;
+    const ENDIANNESS: Endianness = Endianness::Big;
+    const MAX_MESSAGE_LEN: u128 = SHA512_MAX_MESSAGE_LEN;
+    fn compress(state: &mut [u64; 8], blocks: &[u8]) {
+        Sha512::compress_blocks(state, blocks.as_chunks().0);
+    }
+    fn output(state: &[u64; 8]) -> [u8; 64] {
+        let mut final_hash = [0u8; 64];
+        store_words(state, Self::ENDIANNESS, &mut final_hash);
+        final_hash
+    }
+    fn into_hash_state(state: MdHashState) -> HashState {
+        let mut h = [0; 8];
+        load_words(&state.h, Self::ENDIANNESS, &mut h);
+        HashState::Sha512(hash_state::Sha512HashState {
+            h,
+            message_len: state.message_len,
+            block_len: state.block_len,
+            current_block: *state.current_block.first_chunk().unwrap(),
+        })
+    }
+    fn from_hash_state(hash_state: HashState) -> Result<MdHashState, hash_state::Error> {
+        match hash_state {
+            HashState::Sha512(hs) => {
+                let mut h = [0u8; MD_MAX_STATE_SIZE];
+                store_words(&hs.h, Self::ENDIANNESS, &mut h);
+                let mut current_block = [0u8; MD_MAX_BLOCK_SIZE];
+                current_block[..SHA512_BLOCK_SIZE].copy_from_slice(&hs.current_block);
+                Ok(MdHashState {
+                    id: Self::ID,
+                    h,
+                    message_len: hs.message_len,
+                    block_len: hs.block_len,
+                    current_block,
+                })
+            }
+            _ => Err(hash_state::Error::HashTypeNotMatch),
         }
     }
-    /// Compute hash for current block
-    fn process_block(&mut self) {
-        if self.block_len != SHA512_BLOCK_SIZE {
-            panic!("block is not filled");
+}
+/// Calculates SHA-512
+pub type Sha512 = MdHasher<Sha512Compression>;
+
+impl Sha512 {
+    /// Write whole blocks at once.
+    ///
+    /// Blocks are hashed straight from `blocks` unless there are pending bytes in the current block.
+    pub fn update_blocks(&mut self, blocks: &[[u8; SHA512_BLOCK_SIZE]]) -> usize {
+        self.update(blocks.as_flattened())
+    }
+    /// Compute hash for given blocks, using AVX2 if available
+    pub(crate) fn compress_blocks(state: &mut [u64; 8], blocks: &[[u8; SHA512_BLOCK_SIZE]]) {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if simd::avx2_available() {
+                unsafe { simd::compress_sha512(state, blocks) };
+                return;
+            }
         }
-        let mut w = [Word64(0); 80];
-        for t in 0..16 {
-            w[t] = self.get_word64_in_block(t)
+        let mut h = state.map(Word64);
+        for block in blocks {
+            Self::compress(&mut h, block);
         }
-        for t in 16..80 {
-            w[t] = Self::lsigma1(w[t - 2]) + w[t - 7] + Self::lsigma0(w[t - 15]) + w[t - 16];
+        *state = h.map(|w| w.0);
+    }
+    /// Compute hash for given block
+    #[cfg(not(any(feature = "small", feature = "fast")))]
+    pub(crate) fn compress(state: &mut [Word64; 8], block: &[u8; SHA512_BLOCK_SIZE]) {
+        *state = Self::const_compress(Word64::unwrap_array(*state), block).map(Word64);
+    }
+    /// Compute hash for given block, in const context. The portable backend and `oneshot` share it.
+    pub(crate) const fn const_compress(mut state: [u64; 8], block: &[u8; SHA512_BLOCK_SIZE]) -> [u64; 8] {
+        let mut w = [0u64; 80];
+        let mut t = 0;
+        while t < 16 {
+            w[t] = u64::from_be_bytes([
+                block[t * 8],
+                block[t * 8 + 1],
+                block[t * 8 + 2],
+                block[t * 8 + 3],
+                block[t * 8 + 4],
+                block[t * 8 + 5],
+                block[t * 8 + 6],
+                block[t * 8 + 7],
+            ]);
+            t += 1;
         }
-        let mut a = self.h[0];
-        let mut b = self.h[1];
-        let mut c = self.h[2];
-        let mut d = self.h[3];
-        let mut e = self.h[4];
-        let mut f = self.h[5];
-        let mut g = self.h[6];
-        let mut h = self.h[7];
-
-        for t in 0..80 {
-            let t1 = h + Self::sigma1(e) + Self::ch(e, f, g) + SHA512_K[t] + w[t];
-            let t2 = Self::sigma0(a) + Self::maj(a, b, c);
+        while t < 80 {
+            let lsigma0 = w[t - 15].rotate_right(1) ^ w[t - 15].rotate_right(8) ^ (w[t - 15] >> 7);
+            let lsigma1 = w[t - 2].rotate_right(19) ^ w[t - 2].rotate_right(61) ^ (w[t - 2] >> 6);
+            w[t] = lsigma1.wrapping_add(w[t - 7]).wrapping_add(lsigma0).wrapping_add(w[t - 16]);
+            t += 1;
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        let mut t = 0;
+        while t < 80 {
+            let sigma1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(sigma1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA512_K[t].0)
+                .wrapping_add(w[t]);
+            let sigma0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = sigma0.wrapping_add(maj);
             h = g;
             g = f;
             f = e;
-            e = d + t1;
+            e = d.wrapping_add(t1);
             d = c;
             c = b;
             b = a;
-            a = t1 + t2;
+            a = t1.wrapping_add(t2);
+            t += 1;
         }
-        self.h[0] = a + self.h[0];
-        self.h[1] = b + self.h[1];
-        self.h[2] = c + self.h[2];
-        self.h[3] = d + self.h[3];
-        self.h[4] = e + self.h[4];
-        self.h[5] = f + self.h[5];
-        self.h[6] = g + self.h[6];
-        self.h[7] = h + self.h[7];
-
-        self.current_block = [0u8; SHA512_BLOCK_SIZE]; // next block
-        self.block_len = 0; // reset block
+        let hash = [a, b, c, d, e, f, g, h];
+        let mut i = 0;
+        while i < 8 {
+            state[i] = state[i].wrapping_add(hash[i]);
+            i += 1;
+        }
+        state
+    }
+    /// Compute hash for given block, with a rolled loop and 16 words of message schedule
+    #[cfg(feature = "small")]
+    pub(crate) fn compress(state: &mut [Word64; 8], block: &[u8; SHA512_BLOCK_SIZE]) {
+        let mut w = [Word64(0); 16];
+        let mut hash = *state;
+        for t in 0..80 {
+            let wt = if t < 16 {
+                Self::get_word64_in_block(block, t)
+            } else {
+                Self::lsigma1(w[(t + 14) & 15]) + w[(t + 9) & 15] + Self::lsigma0(w[(t + 1) & 15]) + w[t & 15]
+            };
+            w[t & 15] = wt;
+            let [a, b, c, d, e, f, g, h] = hash;
+            let t1 = h + Self::sigma1(e) + Self::ch(e, f, g) + SHA512_K[t] + wt;
+            let t2 = Self::sigma0(a) + Self::maj(a, b, c);
+            hash = [t1 + t2, a, b, c, d + t1, e, f, g];
+        }
+        for (word, x) in state.iter_mut().zip(hash) {
+            *word = x + *word;
+        }
+    }
+    /// Compute hash for given block, with fully unrolled rounds
+    #[cfg(all(feature = "fast", not(feature = "small")))]
+    pub(crate) fn compress(state: &mut [Word64; 8], block: &[u8; SHA512_BLOCK_SIZE]) {
+        let mut w = [Word64(0); 16];
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        sha512_rounds8!(load, w, block, 0, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(load, w, block, 8, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(schedule, w, block, 16, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(schedule, w, block, 24, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(schedule, w, block, 32, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(schedule, w, block, 40, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(schedule, w, block, 48, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(schedule, w, block, 56, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(schedule, w, block, 64, a, b, c, d, e, f, g, h);
+        sha512_rounds8!(schedule, w, block, 72, a, b, c, d, e, f, g, h);
+        state[0] = a + state[0];
+        state[1] = b + state[1];
+        state[2] = c + state[2];
+        state[3] = d + state[3];
+        state[4] = e + state[4];
+        state[5] = f + state[5];
+        state[6] = g + state[6];
+        state[7] = h + state[7];
     }
 
     /// Conbines 8 byte and returns as Word64.
-    const fn get_word64_in_block(&self, i: usize) -> Word64 {
+    #[cfg(any(feature = "small", feature = "fast"))]
+    const fn get_word64_in_block(block: &[u8; SHA512_BLOCK_SIZE], i: usize) -> Word64 {
         let m: u64 =
-              ((self.current_block[i * 8] as u64) << 56)
-            + ((self.current_block[i * 8 + 1] as u64) << 48)
-            + ((self.current_block[i * 8 + 2] as u64) << 40)
-            + ((self.current_block[i * 8 + 3] as u64) << 32)
-            + ((self.current_block[i * 8 + 4] as u64) << 24)
-            + ((self.current_block[i * 8 + 5] as u64) << 16)
-            + ((self.current_block[i * 8 + 6] as u64) << 8)
-            + (self.current_block[i * 8 + 7] as u64);
+              ((block[i * 8] as u64) << 56)
+            + ((block[i * 8 + 1] as u64) << 48)
+            + ((block[i * 8 + 2] as u64) << 40)
+            + ((block[i * 8 + 3] as u64) << 32)
+            + ((block[i * 8 + 4] as u64) << 24)
+            + ((block[i * 8 + 5] as u64) << 16)
+            + ((block[i * 8 + 6] as u64) << 8)
+            + (block[i * 8 + 7] as u64);
         Word64(m)
     }
 }
 
 /// SHA512 functions
+#[cfg(any(feature = "small", feature = "fast"))]
 impl Sha512 {
     fn sigma0(x: Word64) -> Word64 {
         rotr(x, 28) ^ rotr(x, 34) ^ rotr(x, 39)
@@ -108,89 +260,3 @@ impl Sha512 {
         (x & y) ^ (x & z) ^ (y & z)
     }
 }
-impl StreamHasher for Sha512 {
-    type Output = [u8; 64];
-    const BLOCK_SIZE: usize = SHA512_BLOCK_SIZE;
-    fn update(&mut self, buf: &[u8]) -> usize {
-        let len = buf.len();
-        if len == 0 {
-            // if no data or no remaining data, stop
-            return 0;
-        }
-        let writable_len = Self::BLOCK_SIZE - self.block_len;
-        let writable_area = &mut self.current_block[self.block_len..];
-
-        if len >= writable_len {
-            // overflows block or buf.len() == writable_len
-            writable_area.clone_from_slice(&buf[0..writable_len]); // fill block
-            self.block_len += writable_len;
-            self.message_len += writable_len as u128;
-            self.process_block(); // perform hash calculation
-            self.update(&buf[writable_len..]); // recursively write remaining
-        } else {
-            // don't fill block
-            let write_area = &mut self.current_block[self.block_len..self.block_len + len];
-            write_area.clone_from_slice(&buf[..]);
-            self.block_len += len;
-            self.message_len += len as u128;
-        }
-        len
-    }
-    fn finish(mut self) -> Self::Output {
-        self.current_block[self.block_len] = 0x80;
-        if self.block_len + 1 + 16 > Self::BLOCK_SIZE {
-            // data||0x80||size(u128) overflows block
-            
-            self.block_len = Self::BLOCK_SIZE;
-            self.process_block(); // perform hash calculation
-        }
-        let writable_area = &mut self.current_block[Self::BLOCK_SIZE - 16..Self::BLOCK_SIZE];
-        let len_bits = self.message_len * 8;
-        writable_area.clone_from_slice(&len_bits.to_be_bytes());
-        self.block_len = Self::BLOCK_SIZE;
-        self.process_block();
-        let mut final_hash: Self::Output = [0; 64];
-        for i in 0..8 {
-            let word_area = &mut final_hash[i * 8..i * 8 + 8];
-            word_area.clone_from_slice(&self.h[i].0.to_be_bytes());
-        }
-        return final_hash;
-    }
-    
-}
-impl Resumable for Sha512 {
-    fn pause(self) -> HashState {
-        let h: [u64; 8] = [
-            self.h[0].0,
-            self.h[1].0,
-            self.h[2].0,
-            self.h[3].0,
-            self.h[4].0,
-            self.h[5].0,
-            self.h[6].0,
-            self.h[7].0,
-        ];
-        HashState::Sha512(hash_state::Sha512HashState {
-            h,
-            message_len: self.message_len,
-            block_len: self.block_len,
-            current_block: self.current_block,
-        })
-    }
-    fn resume(hash_state: HashState) -> Result<Self, hash_state::Error> {
-        match hash_state {
-            HashState::Sha512(hs) => Ok(Self {
-                h: arr64![hs.h[0], hs.h[1], hs.h[2], hs.h[3], hs.h[4], hs.h[5], hs.h[6], hs.h[7]],
-                message_len: hs.message_len,
-                block_len: hs.block_len,
-                current_block: hs.current_block,
-            }),
-            _ => Err(hash_state::Error::HashTypeNotMatch),
-        }
-    }
-}
-impl Default for Sha512 {
-    fn default() -> Self {
-        Self::new()
-    }
-}
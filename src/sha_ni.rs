@@ -0,0 +1,242 @@
+//! SHA-1 / SHA-256 compression functions using Intel SHA extensions
+//!
+//! The hash values are passed in the same layout as the portable implementation, so hash states are compatible between backends.
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use crate::consts::*;
+
+/// Returns true if SHA extensions can be used.
+///
+/// With `std` feature, CPU features are detected on runtime. Otherwise, they are decided on compile time by `target_feature`.
+#[inline]
+pub fn available() -> bool {
+    #[cfg(feature = "std")]
+    {
+        std::is_x86_feature_detected!("sha")
+            && std::is_x86_feature_detected!("sse2")
+            && std::is_x86_feature_detected!("ssse3")
+            && std::is_x86_feature_detected!("sse4.1")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(all(
+            target_feature = "sha",
+            target_feature = "sse2",
+            target_feature = "ssse3",
+            target_feature = "sse4.1"
+        ))
+    }
+}
+
+macro_rules! sha1_rounds4 {
+    ($h0:ident, $h1:ident, $wk:expr, $i:expr) => {
+        _mm_sha1rnds4_epu32($h0, _mm_sha1nexte_epu32($h1, $wk), $i)
+    };
+}
+
+macro_rules! sha1_schedule {
+    ($v0:expr, $v1:expr, $v2:expr, $v3:expr) => {
+        _mm_sha1msg2_epu32(_mm_xor_si128(_mm_sha1msg1_epu32($v0, $v1), $v2), $v3)
+    };
+}
+
+macro_rules! sha1_schedule_rounds4 {
+    ($h0:ident, $h1:ident, $w0:expr, $w1:expr, $w2:expr, $w3:expr, $w4:expr, $i:expr) => {
+        $w4 = sha1_schedule!($w0, $w1, $w2, $w3);
+        $h1 = sha1_rounds4!($h0, $h1, $w4, $i);
+    };
+}
+
+/// Compute SHA-1 hash for given blocks
+///
+/// # Safety
+/// The CPU must support `sha`, `sse2`, `ssse3` and `sse4.1`. See `available()`.
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+pub unsafe fn compress_sha1(state: &mut [u32; 5], blocks: &[[u8; SHA1_BLOCK_SIZE]]) {
+    // reverses all bytes, as words are stored from high lane to low lane
+    let mask = _mm_set_epi64x(0x0001_0203_0405_0607, 0x0809_0a0b_0c0d_0e0f);
+
+    let mut abcd = _mm_set_epi32(state[0] as i32, state[1] as i32, state[2] as i32, state[3] as i32);
+    let mut e0 = _mm_set_epi32(state[4] as i32, 0, 0, 0);
+
+    for block in blocks {
+        let block_ptr = block.as_ptr() as *const __m128i;
+        let mut w0 = _mm_shuffle_epi8(_mm_loadu_si128(block_ptr), mask);
+        let mut w1 = _mm_shuffle_epi8(_mm_loadu_si128(block_ptr.add(1)), mask);
+        let mut w2 = _mm_shuffle_epi8(_mm_loadu_si128(block_ptr.add(2)), mask);
+        let mut w3 = _mm_shuffle_epi8(_mm_loadu_si128(block_ptr.add(3)), mask);
+        let mut w4;
+
+        let mut h0 = abcd;
+        let mut h1 = _mm_add_epi32(e0, w0);
+
+        // 0..20
+        h1 = _mm_sha1rnds4_epu32(h0, h1, 0);
+        h0 = sha1_rounds4!(h1, h0, w1, 0);
+        h1 = sha1_rounds4!(h0, h1, w2, 0);
+        h0 = sha1_rounds4!(h1, h0, w3, 0);
+        sha1_schedule_rounds4!(h0, h1, w0, w1, w2, w3, w4, 0);
+        // 20..40
+        sha1_schedule_rounds4!(h1, h0, w1, w2, w3, w4, w0, 1);
+        sha1_schedule_rounds4!(h0, h1, w2, w3, w4, w0, w1, 1);
+        sha1_schedule_rounds4!(h1, h0, w3, w4, w0, w1, w2, 1);
+        sha1_schedule_rounds4!(h0, h1, w4, w0, w1, w2, w3, 1);
+        sha1_schedule_rounds4!(h1, h0, w0, w1, w2, w3, w4, 1);
+        // 40..60
+        sha1_schedule_rounds4!(h0, h1, w1, w2, w3, w4, w0, 2);
+        sha1_schedule_rounds4!(h1, h0, w2, w3, w4, w0, w1, 2);
+        sha1_schedule_rounds4!(h0, h1, w3, w4, w0, w1, w2, 2);
+        sha1_schedule_rounds4!(h1, h0, w4, w0, w1, w2, w3, 2);
+        sha1_schedule_rounds4!(h0, h1, w0, w1, w2, w3, w4, 2);
+        // 60..80
+        sha1_schedule_rounds4!(h1, h0, w1, w2, w3, w4, w0, 3);
+        sha1_schedule_rounds4!(h0, h1, w2, w3, w4, w0, w1, 3);
+        sha1_schedule_rounds4!(h1, h0, w3, w4, w0, w1, w2, 3);
+        sha1_schedule_rounds4!(h0, h1, w4, w0, w1, w2, w3, 3);
+        sha1_schedule_rounds4!(h1, h0, w0, w1, w2, w3, w4, 3);
+
+        abcd = _mm_add_epi32(abcd, h0);
+        e0 = _mm_sha1nexte_epu32(h1, e0);
+    }
+
+    state[0] = _mm_extract_epi32(abcd, 3) as u32;
+    state[1] = _mm_extract_epi32(abcd, 2) as u32;
+    state[2] = _mm_extract_epi32(abcd, 1) as u32;
+    state[3] = _mm_extract_epi32(abcd, 0) as u32;
+    state[4] = _mm_extract_epi32(e0, 3) as u32;
+}
+
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+unsafe fn sha256_schedule(v0: __m128i, v1: __m128i, v2: __m128i, v3: __m128i) -> __m128i {
+    let t1 = _mm_sha256msg1_epu32(v0, v1);
+    let t2 = _mm_alignr_epi8(v3, v2, 4);
+    let t3 = _mm_add_epi32(t1, t2);
+    _mm_sha256msg2_epu32(t3, v3)
+}
+
+macro_rules! sha256_rounds4 {
+    ($abef:ident, $cdgh:ident, $w:expr, $i:expr) => {{
+        let k = _mm_set_epi32(
+            SHA256_K[$i * 4 + 3].0 as i32,
+            SHA256_K[$i * 4 + 2].0 as i32,
+            SHA256_K[$i * 4 + 1].0 as i32,
+            SHA256_K[$i * 4].0 as i32,
+        );
+        let wk = _mm_add_epi32($w, k);
+        $cdgh = _mm_sha256rnds2_epu32($cdgh, $abef, wk);
+        $abef = _mm_sha256rnds2_epu32($abef, $cdgh, _mm_shuffle_epi32(wk, 0x0e));
+    }};
+}
+
+macro_rules! sha256_schedule_rounds4 {
+    ($abef:ident, $cdgh:ident, $w0:expr, $w1:expr, $w2:expr, $w3:expr, $w4:expr, $i:expr) => {{
+        $w4 = sha256_schedule($w0, $w1, $w2, $w3);
+        sha256_rounds4!($abef, $cdgh, $w4, $i);
+    }};
+}
+
+/// Compute SHA-256 hash for given blocks
+///
+/// # Safety
+/// The CPU must support `sha`, `sse2`, `ssse3` and `sse4.1`. See `available()`.
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+#[allow(unused_assignments)]
+pub unsafe fn compress_sha256(state: &mut [u32; 8], blocks: &[[u8; SHA256_BLOCK_SIZE]]) {
+    // converts each word from big endian
+    let mask = _mm_set_epi64x(0x0c0d_0e0f_0809_0a0b, 0x0405_0607_0001_0203);
+
+    let state_ptr = state.as_ptr() as *const __m128i;
+    let dcba = _mm_loadu_si128(state_ptr);
+    let efgh = _mm_loadu_si128(state_ptr.add(1));
+
+    let cdab = _mm_shuffle_epi32(dcba, 0xb1);
+    let efgh = _mm_shuffle_epi32(efgh, 0x1b);
+    let mut abef = _mm_alignr_epi8(cdab, efgh, 8);
+    let mut cdgh = _mm_blend_epi16(efgh, cdab, 0xf0);
+
+    for block in blocks {
+        let abef_save = abef;
+        let cdgh_save = cdgh;
+
+        let block_ptr = block.as_ptr() as *const __m128i;
+        let mut w0 = _mm_shuffle_epi8(_mm_loadu_si128(block_ptr), mask);
+        let mut w1 = _mm_shuffle_epi8(_mm_loadu_si128(block_ptr.add(1)), mask);
+        let mut w2 = _mm_shuffle_epi8(_mm_loadu_si128(block_ptr.add(2)), mask);
+        let mut w3 = _mm_shuffle_epi8(_mm_loadu_si128(block_ptr.add(3)), mask);
+        let mut w4;
+
+        sha256_rounds4!(abef, cdgh, w0, 0);
+        sha256_rounds4!(abef, cdgh, w1, 1);
+        sha256_rounds4!(abef, cdgh, w2, 2);
+        sha256_rounds4!(abef, cdgh, w3, 3);
+        sha256_schedule_rounds4!(abef, cdgh, w0, w1, w2, w3, w4, 4);
+        sha256_schedule_rounds4!(abef, cdgh, w1, w2, w3, w4, w0, 5);
+        sha256_schedule_rounds4!(abef, cdgh, w2, w3, w4, w0, w1, 6);
+        sha256_schedule_rounds4!(abef, cdgh, w3, w4, w0, w1, w2, 7);
+        sha256_schedule_rounds4!(abef, cdgh, w4, w0, w1, w2, w3, 8);
+        sha256_schedule_rounds4!(abef, cdgh, w0, w1, w2, w3, w4, 9);
+        sha256_schedule_rounds4!(abef, cdgh, w1, w2, w3, w4, w0, 10);
+        sha256_schedule_rounds4!(abef, cdgh, w2, w3, w4, w0, w1, 11);
+        sha256_schedule_rounds4!(abef, cdgh, w3, w4, w0, w1, w2, 12);
+        sha256_schedule_rounds4!(abef, cdgh, w4, w0, w1, w2, w3, 13);
+        sha256_schedule_rounds4!(abef, cdgh, w0, w1, w2, w3, w4, 14);
+        sha256_schedule_rounds4!(abef, cdgh, w1, w2, w3, w4, w0, 15);
+
+        abef = _mm_add_epi32(abef, abef_save);
+        cdgh = _mm_add_epi32(cdgh, cdgh_save);
+    }
+
+    let feba = _mm_shuffle_epi32(abef, 0x1b);
+    let dchg = _mm_shuffle_epi32(cdgh, 0xb1);
+    let dcba = _mm_blend_epi16(feba, dchg, 0xf0);
+    let hgef = _mm_alignr_epi8(dchg, feba, 8);
+
+    let state_ptr = state.as_mut_ptr() as *mut __m128i;
+    _mm_storeu_si128(state_ptr, dcba);
+    _mm_storeu_si128(state_ptr.add(1), hgef);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::arith::Word32;
+    use crate::{Sha1, Sha256};
+
+    fn blocks() -> [[u8; 64]; 3] {
+        let mut blocks = [[0u8; 64]; 3];
+        for (i, b) in blocks.iter_mut().flatten().enumerate() {
+            *b = (i * 7 + 3) as u8;
+        }
+        blocks
+    }
+    #[test]
+    fn sha1_matches_portable() {
+        if !std::is_x86_feature_detected!("sha") || !std::is_x86_feature_detected!("sse4.1") {
+            return;
+        }
+        let mut expected = SHA1_H;
+        for block in blocks().iter() {
+            Sha1::compress(&mut expected, block);
+        }
+        let mut state = SHA1_H.map(|w| w.0);
+        unsafe { compress_sha1(&mut state, &blocks()) };
+        assert_eq!(state.map(Word32), expected);
+    }
+    #[test]
+    fn sha256_matches_portable() {
+        if !std::is_x86_feature_detected!("sha") || !std::is_x86_feature_detected!("sse4.1") {
+            return;
+        }
+        let mut expected = SHA256_H;
+        for block in blocks().iter() {
+            Sha256::compress(&mut expected, block);
+        }
+        let mut state = SHA256_H.map(|w| w.0);
+        unsafe { compress_sha256(&mut state, &blocks()) };
+        assert_eq!(state.map(Word32), expected);
+    }
+}
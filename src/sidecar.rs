@@ -0,0 +1,85 @@
+//! Sidecar files of `checkpoint` and `append`
+//!
+//! A sidecar consists of magic bytes with a version, the content, and the SHA-256 of both. It's written to
+//! `<path>.tmp`, synced and atomically renamed to `<path>`, so it always holds a complete content.
+use crate::hash_state::HashState;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+const CHECKSUM_SIZE: usize = 32;
+
+/// Appends the length of the encoded `state` (u16 BE) and the encoded `state`, or fails with `InvalidInput` if
+/// `state` can't be encoded.
+pub(crate) fn push_state(bytes: &mut Vec<u8>, state: &HashState) -> io::Result<()> {
+    let mut encoded = [0u8; HashState::MAX_ENCODED_LEN];
+    let len = state
+        .encode(&mut encoded)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid hash state"))?;
+    bytes.extend_from_slice(&(len as u16).to_be_bytes());
+    bytes.extend_from_slice(&encoded[..len]);
+    Ok(())
+}
+
+/// Parses the output of `push_state`, which must be the rest of `bytes`, or returns `None` if it's invalid.
+pub(crate) fn parse_state(bytes: &[u8]) -> Option<HashState> {
+    if bytes.len() < 2 || bytes.len() - 2 != u16::from_be_bytes([bytes[0], bytes[1]]) as usize {
+        return None;
+    }
+    HashState::decode(&bytes[2..]).ok()
+}
+
+/// Reads a sidecar written by `write`, and returns the content, or `None` if `magic` or the checksum doesn't match.
+pub(crate) fn read(path: &Path, magic: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut bytes = fs::read(path)?;
+    if bytes.len() < magic.len() + CHECKSUM_SIZE || &bytes[..magic.len()] != magic {
+        return Ok(None);
+    }
+    let (content, checksum) = bytes.split_at(bytes.len() - CHECKSUM_SIZE);
+    if crate::sha256(content) != checksum {
+        return Ok(None);
+    }
+    bytes.truncate(bytes.len() - CHECKSUM_SIZE);
+    bytes.drain(..magic.len());
+    Ok(Some(bytes))
+}
+
+/// Atomically writes `magic`, `content` and the SHA-256 of both to `path`, through `<path>.tmp`.
+pub(crate) fn write(path: &Path, magic: &[u8], content: &[u8]) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(magic.len() + content.len() + CHECKSUM_SIZE);
+    bytes.extend_from_slice(magic);
+    bytes.extend_from_slice(content);
+    let checksum = crate::sha256(&bytes);
+    bytes.extend_from_slice(&checksum);
+
+    let tmp_path = tmp_path(path);
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    sync_parent(path)
+}
+
+/// Syncs the directory containing `path`, so that a rename into it survives a crash
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+/// Directories can't be opened for syncing on other platforms, where renames are journaled by the file system
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp_path = OsString::from(path.as_os_str());
+    tmp_path.push(".tmp");
+    PathBuf::from(tmp_path)
+}
@@ -0,0 +1,293 @@
+//! SIMD compression functions using SSE4.1 / AVX2
+//!
+//! For multi-lane SHA-256, each vector holds the same word of independent messages, one message per lane.
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use crate::consts::*;
+
+/// Returns true if the 4-lane SSE4.1 backend can be used.
+#[inline]
+pub fn sse41_available() -> bool {
+    #[cfg(feature = "std")]
+    {
+        std::is_x86_feature_detected!("sse4.1")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "sse4.1")
+    }
+}
+
+/// Returns true if the 8-lane AVX2 backend can be used.
+#[inline]
+pub fn avx2_available() -> bool {
+    #[cfg(feature = "std")]
+    {
+        std::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "avx2")
+    }
+}
+
+macro_rules! define_sha256_multi_lane {
+    (
+        $name:ident, $feature:literal, $lanes:literal, $vec:ty,
+        $loadu:ident, $storeu:ident, $set1:ident, $add:ident, $xor:ident, $and:ident, $andnot:ident, $or:ident, $srli:ident, $slli:ident
+    ) => {
+        /// Compute SHA-256 hash for one block of each lane
+        ///
+        /// # Safety
+        #[doc = concat!("The CPU must support `", $feature, "`.")]
+        #[target_feature(enable = $feature)]
+        pub unsafe fn $name(states: &mut [[u32; 8]; $lanes], blocks: [&[u8; SHA256_BLOCK_SIZE]; $lanes]) {
+            #[target_feature(enable = $feature)]
+            #[inline]
+            unsafe fn rotr<const R: i32, const L: i32>(x: $vec) -> $vec {
+                $or($srli::<R>(x), $slli::<L>(x))
+            }
+            #[target_feature(enable = $feature)]
+            #[inline]
+            unsafe fn load_lanes(words: [u32; $lanes]) -> $vec {
+                $loadu(words.as_ptr() as *const $vec)
+            }
+
+            let mut w = [$set1(0); 64];
+            for t in 0..16 {
+                w[t] = load_lanes(core::array::from_fn(|lane| {
+                    let word = &blocks[lane][t * 4..t * 4 + 4];
+                    u32::from_be_bytes([word[0], word[1], word[2], word[3]])
+                }));
+            }
+            for t in 16..64 {
+                let x = w[t - 2];
+                let lsigma1 = $xor($xor(rotr::<17, 15>(x), rotr::<19, 13>(x)), $srli::<10>(x));
+                let x = w[t - 15];
+                let lsigma0 = $xor($xor(rotr::<7, 25>(x), rotr::<18, 14>(x)), $srli::<3>(x));
+                w[t] = $add($add(lsigma1, w[t - 7]), $add(lsigma0, w[t - 16]));
+            }
+
+            let mut hash = [$set1(0); 8];
+            for (i, word) in hash.iter_mut().enumerate() {
+                *word = load_lanes(core::array::from_fn(|lane| states[lane][i]));
+            }
+            let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hash;
+            for t in 0..64 {
+                let sigma1 = $xor($xor(rotr::<6, 26>(e), rotr::<11, 21>(e)), rotr::<25, 7>(e));
+                let ch = $xor($and(e, f), $andnot(e, g));
+                let k = $set1(SHA256_K[t].0 as i32);
+                let t1 = $add($add($add(h, sigma1), $add(ch, k)), w[t]);
+                let sigma0 = $xor($xor(rotr::<2, 30>(a), rotr::<13, 19>(a)), rotr::<22, 10>(a));
+                let maj = $xor($xor($and(a, b), $and(a, c)), $and(b, c));
+                let t2 = $add(sigma0, maj);
+                h = g;
+                g = f;
+                f = e;
+                e = $add(d, t1);
+                d = c;
+                c = b;
+                b = a;
+                a = $add(t1, t2);
+            }
+            for (i, word) in [a, b, c, d, e, f, g, h].iter().enumerate() {
+                let mut words = [0u32; $lanes];
+                $storeu(words.as_mut_ptr() as *mut $vec, $add(*word, hash[i]));
+                for (lane, state) in states.iter_mut().enumerate() {
+                    state[i] = words[lane];
+                }
+            }
+        }
+    };
+}
+
+define_sha256_multi_lane!(
+    compress_sha256x4, "sse2,sse4.1", 4, __m128i,
+    _mm_loadu_si128, _mm_storeu_si128, _mm_set1_epi32, _mm_add_epi32, _mm_xor_si128,
+    _mm_and_si128, _mm_andnot_si128, _mm_or_si128, _mm_srli_epi32, _mm_slli_epi32
+);
+define_sha256_multi_lane!(
+    compress_sha256x8, "avx2", 8, __m256i,
+    _mm256_loadu_si256, _mm256_storeu_si256, _mm256_set1_epi32, _mm256_add_epi32, _mm256_xor_si256,
+    _mm256_and_si256, _mm256_andnot_si256, _mm256_or_si256, _mm256_srli_epi32, _mm256_slli_epi32
+);
+
+#[target_feature(enable = "avx2")]
+#[inline]
+unsafe fn sha512_lsigma0(x: __m128i) -> __m128i {
+    let rotr1 = _mm_or_si128(_mm_srli_epi64(x, 1), _mm_slli_epi64(x, 63));
+    let rotr8 = _mm_or_si128(_mm_srli_epi64(x, 8), _mm_slli_epi64(x, 56));
+    _mm_xor_si128(_mm_xor_si128(rotr1, rotr8), _mm_srli_epi64(x, 7))
+}
+#[target_feature(enable = "avx2")]
+#[inline]
+unsafe fn sha512_lsigma1(x: __m128i) -> __m128i {
+    let rotr19 = _mm_or_si128(_mm_srli_epi64(x, 19), _mm_slli_epi64(x, 45));
+    let rotr61 = _mm_or_si128(_mm_srli_epi64(x, 61), _mm_slli_epi64(x, 3));
+    _mm_xor_si128(_mm_xor_si128(rotr19, rotr61), _mm_srli_epi64(x, 6))
+}
+
+/// Compute SHA-512 hash for given blocks, with a vectorized message schedule
+///
+/// `w[t]` depends on `w[t - 2]`, so the schedule is computed two words at a time with 128-bit operations.
+/// The 256-bit operations only byte-swap the block and add the round constants, four words at a time.
+///
+/// # Safety
+/// The CPU must support `avx2`.
+#[target_feature(enable = "avx2")]
+pub unsafe fn compress_sha512(state: &mut [u64; 8], blocks: &[[u8; SHA512_BLOCK_SIZE]]) {
+    // converts each word from big endian
+    let mask = _mm256_set_epi64x(
+        0x0809_0a0b_0c0d_0e0f,
+        0x0001_0203_0405_0607,
+        0x0809_0a0b_0c0d_0e0f,
+        0x0001_0203_0405_0607,
+    );
+    let mut w = [0u64; 80];
+    let mut wk = [0u64; 80];
+    for block in blocks {
+        let block_ptr = block.as_ptr() as *const __m256i;
+        let w_ptr = w.as_mut_ptr();
+        for i in 0..4 {
+            let words = _mm256_shuffle_epi8(_mm256_loadu_si256(block_ptr.add(i)), mask);
+            _mm256_storeu_si256(w_ptr.add(i * 4) as *mut __m256i, words);
+        }
+        // w[t] depends on w[t - 2], so two words are scheduled at once
+        for t in (16..80).step_by(2) {
+            let w2 = _mm_loadu_si128(w_ptr.add(t - 2) as *const __m128i);
+            let w7 = _mm_loadu_si128(w_ptr.add(t - 7) as *const __m128i);
+            let w15 = _mm_loadu_si128(w_ptr.add(t - 15) as *const __m128i);
+            let w16 = _mm_loadu_si128(w_ptr.add(t - 16) as *const __m128i);
+            let words = _mm_add_epi64(
+                _mm_add_epi64(sha512_lsigma1(w2), w7),
+                _mm_add_epi64(sha512_lsigma0(w15), w16),
+            );
+            _mm_storeu_si128(w_ptr.add(t) as *mut __m128i, words);
+        }
+        for t in (0..80).step_by(4) {
+            let k = _mm256_set_epi64x(
+                SHA512_K[t + 3].0 as i64,
+                SHA512_K[t + 2].0 as i64,
+                SHA512_K[t + 1].0 as i64,
+                SHA512_K[t].0 as i64,
+            );
+            let words = _mm256_loadu_si256(w_ptr.add(t) as *const __m256i);
+            _mm256_storeu_si256(wk.as_mut_ptr().add(t) as *mut __m256i, _mm256_add_epi64(words, k));
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for wk in wk.iter() {
+            let sigma1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(sigma1).wrapping_add(ch).wrapping_add(*wk);
+            let sigma0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = sigma0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, x) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(x);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::arith::{Word32, Word64};
+    use crate::{Sha256, Sha512};
+
+    fn blocks<const N: usize>() -> [[u8; 64]; N] {
+        let mut blocks = [[0u8; 64]; N];
+        for (i, b) in blocks.iter_mut().flatten().enumerate() {
+            *b = (i * 7 + 3) as u8;
+        }
+        blocks
+    }
+    fn portable<const N: usize>(blocks: &[[u8; 64]; N]) -> [[u32; 8]; N] {
+        core::array::from_fn(|lane| {
+            let mut state = SHA256_H;
+            Sha256::compress(&mut state, &blocks[lane]);
+            state.map(|w: Word32| w.0)
+        })
+    }
+    #[test]
+    fn sha256x4_matches_portable() {
+        if !std::is_x86_feature_detected!("sse4.1") {
+            return;
+        }
+        let blocks = blocks::<4>();
+        let mut states = [SHA256_H.map(|w| w.0); 4];
+        unsafe { compress_sha256x4(&mut states, blocks.each_ref()) };
+        assert_eq!(states, portable(&blocks));
+    }
+    // the lanes start from different states and chain over several blocks
+    fn chained_states<const N: usize>(compress: unsafe fn(&mut [[u32; 8]; N], [&[u8; 64]; N])) {
+        let rounds = blocks::<5>();
+        let mut expected: [[Word32; 8]; N] = [SHA256_H; N];
+        for (lane, state) in expected.iter_mut().enumerate() {
+            for block in &rounds[..lane % rounds.len()] {
+                Sha256::compress(state, block);
+            }
+        }
+        let mut states = expected.map(|state| state.map(|w| w.0));
+        for round in 0..rounds.len() {
+            let blocks: [[u8; 64]; N] = core::array::from_fn(|lane| rounds[(round + lane) % rounds.len()]);
+            unsafe { compress(&mut states, blocks.each_ref()) };
+            for (state, block) in expected.iter_mut().zip(&blocks) {
+                Sha256::compress(state, block);
+            }
+            assert_eq!(states, expected.map(|state| state.map(|w| w.0)));
+        }
+    }
+    #[test]
+    fn sha256x4_chains_blocks_per_lane() {
+        if std::is_x86_feature_detected!("sse4.1") {
+            chained_states::<4>(compress_sha256x4);
+        }
+    }
+    #[test]
+    fn sha256x8_chains_blocks_per_lane() {
+        if std::is_x86_feature_detected!("avx2") {
+            chained_states::<8>(compress_sha256x8);
+        }
+    }
+    #[test]
+    fn sha512_matches_portable() {
+        if !std::is_x86_feature_detected!("avx2") {
+            return;
+        }
+        let mut blocks = [[0u8; 128]; 3];
+        for (i, b) in blocks.iter_mut().flatten().enumerate() {
+            *b = (i * 7 + 3) as u8;
+        }
+        let mut expected = SHA512_H;
+        for block in blocks.iter() {
+            Sha512::compress(&mut expected, block);
+        }
+        let mut state = SHA512_H.map(|w| w.0);
+        unsafe { compress_sha512(&mut state, &blocks) };
+        assert_eq!(state.map(Word64), expected);
+    }
+    #[test]
+    fn sha256x8_matches_portable() {
+        if !std::is_x86_feature_detected!("avx2") {
+            return;
+        }
+        let blocks = blocks::<8>();
+        let mut states = [SHA256_H.map(|w| w.0); 8];
+        unsafe { compress_sha256x8(&mut states, blocks.each_ref()) };
+        assert_eq!(states, portable(&blocks));
+    }
+}
@@ -1,6 +1,7 @@
 //! Traits
 use crate::hash_state;
 use crate::hash_state::HashState;
+use core::ops::Deref;
 
 /// Trait for streamed hashing state controlling functions.
 pub trait StreamHasher {
@@ -8,12 +9,40 @@ pub trait StreamHasher {
     const BLOCK_SIZE: usize;
     /// write to pending block, process block, create new block. Never finish with pending block is filled. End with new empty block if block filled.
     fn update(&mut self, buf: &[u8]) -> usize;
+    /// Write the concatenation of `bufs`, e.g. `&[&[u8]]` or `&[IoSlice]`, and return the total length. See `update`.
+    fn update_vectored<B: Deref<Target = [u8]>>(&mut self, bufs: &[B]) -> usize {
+        bufs.iter().map(|buf| self.update(buf)).sum()
+    }
+    /// Same as `update`, but refuses to write when the message would exceed the max length defined in fips 180-4.
+    ///
+    /// NOTE: `update` keeps accepting data past the limit, but the state is marked as overflowed and `finish` will panic
+    /// (`try_finish` will fail).
+    ///
+    /// The default implementation is for hashers without a length limit, and always writes.
+    fn try_update(&mut self, buf: &[u8]) -> Result<usize, LengthLimitExceeded> {
+        Ok(self.update(buf))
+    }
     /// Pad and process the last pending block then output the final hash.
     ///
     /// NOTE: After finished, the struct will be moved out, making it unreusable.
+    ///
+    /// Panics if the message has exceeded the max length.
     fn finish(self) -> Self::Output;
+    /// Same as `finish`, but fails instead of panicking if the message has exceeded the max length.
+    ///
+    /// The default implementation is for hashers without a length limit, and always finishes.
+    fn try_finish(self) -> Result<Self::Output, LengthLimitExceeded>
+    where
+        Self: Sized,
+    {
+        Ok(self.finish())
+    }
 }
 
+/// Error returned when the message length exceeds the max length defined in fips 180-4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthLimitExceeded;
+
 /// Trait for pausing and resuming the hashing state.
 pub trait Resumable: Sized {
     /// Returns the current hash state.
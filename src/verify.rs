@@ -0,0 +1,146 @@
+//! Streaming verification against an expected digest
+//!
+//! ```rust
+//! use streamsha::verify::Verifier;
+//! use streamsha::{output, Digest, Sha256};
+//!
+//! let expected = Digest::new(output::hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+//! let mut verifier = Verifier::<Sha256>::new(expected);
+//! verifier.update(b"ab");
+//! verifier.update(b"c");
+//! assert!(verifier.verify().is_ok());
+//! ```
+use crate::output::{Digest, DigestBytes};
+use crate::traits::StreamHasher;
+use core::fmt;
+
+/// The digest of the data doesn't match the expected one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestMismatch;
+
+impl fmt::Display for DigestMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("digest mismatch")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DigestMismatch {}
+
+/// Hashes data and compares the digest with the expected one in constant time
+pub struct Verifier<H: StreamHasher>
+where
+    H::Output: DigestBytes,
+{
+    hasher: H,
+    expected: Digest<H>,
+}
+
+impl<H: StreamHasher + Default> Verifier<H>
+where
+    H::Output: DigestBytes,
+{
+    /// Create new instance expecting `expected`
+    pub fn new(expected: Digest<H>) -> Self {
+        Self::with_hasher(H::default(), expected)
+    }
+}
+
+impl<H: StreamHasher> Verifier<H>
+where
+    H::Output: DigestBytes,
+{
+    /// Create new instance continuing from `hasher`, e.g. a resumed one
+    pub fn with_hasher(hasher: H, expected: Digest<H>) -> Self {
+        Self { hasher, expected }
+    }
+    /// Write to buffer. See `StreamHasher::update`.
+    pub fn update(&mut self, buf: &[u8]) -> usize {
+        self.hasher.update(buf)
+    }
+    /// Finishes hashing and compares the digest with the expected one.
+    pub fn verify(self) -> Result<(), DigestMismatch> {
+        if Digest::<H>::new(self.hasher.finish()) == self.expected {
+            Ok(())
+        } else {
+            Err(DigestMismatch)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::reader::VerifyingReader;
+
+#[cfg(feature = "std")]
+mod reader {
+    use super::{DigestMismatch, Verifier};
+    use crate::output::{Digest, DigestBytes};
+    use crate::traits::StreamHasher;
+    use std::io::{self, Read};
+
+    /// A reader that hashes while reading, and fails with `io::ErrorKind::InvalidData` at EOF if the digest doesn't match
+    ///
+    /// Data is passed through before it's verified, so it must not be trusted until EOF is reached without an error.
+    pub struct VerifyingReader<R: Read, H: StreamHasher>
+    where
+        H::Output: DigestBytes,
+    {
+        inner: R,
+        verifier: Option<Verifier<H>>,
+        result: Result<(), DigestMismatch>,
+    }
+
+    impl<R: Read, H: StreamHasher + Default> VerifyingReader<R, H>
+    where
+        H::Output: DigestBytes,
+    {
+        /// Create new instance reading from `inner` and expecting `expected`
+        pub fn new(inner: R, expected: Digest<H>) -> Self {
+            Self::with_verifier(inner, Verifier::new(expected))
+        }
+    }
+
+    impl<R: Read, H: StreamHasher> VerifyingReader<R, H>
+    where
+        H::Output: DigestBytes,
+    {
+        /// Create new instance reading from `inner` and verifying with `verifier`
+        pub fn with_verifier(inner: R, verifier: Verifier<H>) -> Self {
+            Self {
+                inner,
+                verifier: Some(verifier),
+                result: Ok(()),
+            }
+        }
+        /// Returns `true` once EOF has been reached and the digest matched.
+        pub fn is_verified(&self) -> bool {
+            self.verifier.is_none() && self.result.is_ok()
+        }
+        /// Unwraps the inner reader
+        pub fn into_inner(self) -> R {
+            self.inner
+        }
+    }
+
+    impl<R: Read, H: StreamHasher> Read for VerifyingReader<R, H>
+    where
+        H::Output: DigestBytes,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.result.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let len = self.inner.read(buf)?;
+            if len > 0 {
+                if let Some(verifier) = &mut self.verifier {
+                    verifier.update(&buf[..len]);
+                }
+            } else if !buf.is_empty() {
+                // EOF
+                if let Some(verifier) = self.verifier.take() {
+                    self.result = verifier.verify();
+                    self.result.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                }
+            }
+            Ok(len)
+        }
+    }
+}
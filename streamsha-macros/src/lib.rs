@@ -0,0 +1,147 @@
+//! # streamsha-macros
+//! Compile-time file hashing with streamsha.
+//!
+//! Paths are relative to the directory containing the invoking crate's `Cargo.toml`.
+//! The files are included with `include_bytes!`, so the crate is rebuilt whenever they change.
+//!
+//! ```rust,ignore
+//! use streamsha_macros::{include_bytes_sha256, verify_sha256};
+//!
+//! // fails to compile if the blob drifts
+//! verify_sha256!("firmware/blob.bin", "e3b0c442 98fc1c14 9afbf4c8 996fb924 27ae41e4 649b934c a495991b 7852b855");
+//!
+//! let (bytes, digest): (&[u8; 1024], [u8; 32]) = include_bytes_sha256!("certs/root.der");
+//! ```
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use std::path::PathBuf;
+use streamsha::traits::StreamHasher;
+use streamsha::Sha256;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, LitStr, Token};
+
+/// Includes a file as a reference to a byte array, together with its SHA-256 digest.
+///
+/// Expands to an expression of type `(&'static [u8; N], [u8; 32])`.
+#[proc_macro]
+pub fn include_bytes_sha256(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+    let (full_path, bytes) = match read_file(&path) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let digest = sha256(&bytes);
+    quote!((::core::include_bytes!(#full_path), [#(#digest),*])).into()
+}
+
+/// Fails compilation if the SHA-256 digest of a file doesn't match the expected hex string.
+///
+/// Whitespace in the hex string is ignored. Expands to an item, so it can be placed at module level.
+///
+/// ```rust
+/// # use streamsha_macros::verify_sha256;
+/// verify_sha256!("tests/data/abc.txt", "ba7816bf 8f01cfea 414140de 5dae2223 b00361a3 96177a9c b410ff61 f20015ad");
+/// ```
+///
+/// A mismatch fails compilation:
+///
+/// ```rust,compile_fail
+/// # use streamsha_macros::verify_sha256;
+/// verify_sha256!("tests/data/abc.txt", "ba7816bf 8f01cfea 414140de 5dae2223 b00361a3 96177a9c b410ff61 f20015ae");
+/// ```
+///
+/// So does a missing file:
+///
+/// ```rust,compile_fail
+/// # use streamsha_macros::verify_sha256;
+/// verify_sha256!("tests/data/missing.txt", "ba7816bf 8f01cfea 414140de 5dae2223 b00361a3 96177a9c b410ff61 f20015ad");
+/// ```
+#[proc_macro]
+pub fn verify_sha256(input: TokenStream) -> TokenStream {
+    let VerifyInput { path, expected } = parse_macro_input!(input as VerifyInput);
+    let expected_digest = match parse_hex(&expected) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let (full_path, bytes) = match read_file(&path) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let digest = sha256(&bytes);
+    if digest != expected_digest {
+        return syn::Error::new(
+            path.span(),
+            format!(
+                "SHA-256 mismatch for {}: expected {}, got {}",
+                path.value(),
+                to_hex(&expected_digest),
+                to_hex(&digest)
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+    quote!(const _: &[u8] = ::core::include_bytes!(#full_path);).into()
+}
+
+struct VerifyInput {
+    path: LitStr,
+    expected: LitStr,
+}
+
+impl Parse for VerifyInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let expected = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(VerifyInput { path, expected })
+    }
+}
+
+fn read_file(path: &LitStr) -> syn::Result<(String, Vec<u8>)> {
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
+        .ok_or_else(|| syn::Error::new(Span::call_site(), "CARGO_MANIFEST_DIR is not set"))?;
+    let full_path = PathBuf::from(manifest_dir).join(path.value());
+    let bytes = std::fs::read(&full_path)
+        .map_err(|e| syn::Error::new(path.span(), format!("couldn't read {}: {}", full_path.display(), e)))?;
+    let full_path = full_path
+        .to_str()
+        .ok_or_else(|| syn::Error::new(path.span(), "path is not valid UTF-8"))?
+        .to_owned();
+    Ok((full_path, bytes))
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.finish()
+}
+
+fn parse_hex(lit: &LitStr) -> syn::Result<[u8; 32]> {
+    let digits: Vec<u8> = lit
+        .value()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()
+        .ok_or_else(|| syn::Error::new(lit.span(), "expected digest contains a non-hex character"))?;
+    if digits.len() != 64 {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!("expected 64 hex digits for SHA-256, got {}", digits.len()),
+        ));
+    }
+    let mut digest = [0u8; 32];
+    for (byte, pair) in digest.iter_mut().zip(digits.chunks(2)) {
+        *byte = pair[0] << 4 | pair[1];
+    }
+    Ok(digest)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
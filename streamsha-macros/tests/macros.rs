@@ -0,0 +1,21 @@
+use streamsha_macros::{include_bytes_sha256, verify_sha256};
+
+verify_sha256!(
+    "tests/data/abc.txt",
+    "ba7816bf 8f01cfea 414140de 5dae2223 b00361a3 96177a9c b410ff61 f20015ad"
+);
+
+#[test]
+fn it_includes_bytes_and_digest() {
+    let (bytes, digest) = include_bytes_sha256!("tests/data/abc.txt");
+    assert_eq!(bytes, b"abc");
+    assert_eq!(digest, streamsha::sha256(b"abc"));
+}
+
+#[test]
+fn it_verifies_in_function_body() {
+    verify_sha256!(
+        "tests/data/abc.txt",
+        "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD",
+    );
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "std")]
+use std::fs::{self, File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use streamsha::append::AppendRehasher;
+use streamsha::traits::{Resumable, StreamHasher};
+use streamsha::*;
+
+fn data() -> Vec<u8> {
+    (0..100000).map(|i| (i * 13) as u8).collect()
+}
+
+fn log_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("streamsha-{}-{}.log", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn append(path: &PathBuf, bytes: &[u8]) {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(bytes).unwrap();
+    // make sure mtime moves forward on coarse filesystems
+    file.set_modified(SystemTime::now() + Duration::from_secs(1)).unwrap();
+}
+
+#[test]
+fn it_hashes_only_appended_bytes() {
+    let data = data();
+    let path = log_file("append");
+    fs::write(&path, &data[..30000]).unwrap();
+    let rehasher = AppendRehasher::new(&path).with_spot_check(1000);
+
+    let rehash = rehasher.rehash::<Sha256>().unwrap();
+    assert_eq!((rehash.digest, rehash.len, rehash.hashed), (sha256(&data[..30000]), 30000, 30000));
+
+    let rehash = rehasher.rehash::<Sha256>().unwrap();
+    assert_eq!((rehash.digest, rehash.hashed), (sha256(&data[..30000]), 0));
+
+    append(&path, &data[30000..30001]);
+    let rehash = rehasher.rehash::<Sha256>().unwrap();
+    assert_eq!((rehash.digest, rehash.hashed), (sha256(&data[..30001]), 1));
+
+    append(&path, &data[30001..]);
+    let rehash = rehasher.rehash::<Sha256>().unwrap();
+    assert_eq!((rehash.digest, rehash.len, rehash.hashed), (sha256(&data), 100000, 69999));
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(path.with_extension("log.hashstate")).unwrap();
+}
+
+#[test]
+fn it_rehashes_changed_prefix() {
+    let data = data();
+    let path = log_file("changed");
+    let sidecar = path.with_extension("state");
+    fs::write(&path, &data[..50000]).unwrap();
+    let rehasher = AppendRehasher::new(&path).with_sidecar(&sidecar).with_spot_check(100);
+    rehasher.rehash::<Sha512>().unwrap();
+
+    // rewritten in place within the spot-checked window, then appended
+    let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+    file.seek(SeekFrom::Start(49950)).unwrap();
+    file.write_all(b"x").unwrap();
+    drop(file);
+    append(&path, &data[50000..60000]);
+    let mut expected = data[..60000].to_vec();
+    expected[49950] = b'x';
+    let rehash = rehasher.rehash::<Sha512>().unwrap();
+    assert_eq!((rehash.digest, rehash.hashed), (sha512(&expected), 60000));
+
+    // truncated
+    File::create(&path).unwrap().write_all(&data[..10]).unwrap();
+    let rehash = rehasher.rehash::<Sha512>().unwrap();
+    assert_eq!((rehash.digest, rehash.hashed), (sha512(&data[..10]), 10));
+
+    // sidecar of another hash
+    let rehash = rehasher.rehash::<Sha1>().unwrap();
+    assert_eq!((rehash.digest, rehash.hashed), (sha1(&data[..10]), 10));
+
+    // corrupted sidecar
+    let mut bytes = fs::read(&sidecar).unwrap();
+    bytes[7] ^= 1;
+    fs::write(&sidecar, &bytes).unwrap();
+    let rehash = rehasher.rehash::<Sha1>().unwrap();
+    assert_eq!(rehash.hashed, 10);
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&sidecar).unwrap();
+}
+
+/// SHA-1 that is only resumed for digital signatures
+#[derive(Clone, Default)]
+struct SignatureSha1(Sha1);
+
+impl StreamHasher for SignatureSha1 {
+    type Output = [u8; 20];
+    const BLOCK_SIZE: usize = 64;
+    fn update(&mut self, buf: &[u8]) -> usize {
+        self.0.update(buf)
+    }
+    fn finish(self) -> [u8; 20] {
+        self.0.finish()
+    }
+}
+
+impl Resumable for SignatureSha1 {
+    fn pause(self) -> hash_state::HashState {
+        self.0.pause()
+    }
+    fn resume(hash_state: hash_state::HashState) -> Result<Self, hash_state::Error> {
+        policy::set(policy::Policy::NoSha1Signatures);
+        let resumed = Sha1::resume_with_purpose(hash_state, policy::Purpose::DigitalSignature);
+        policy::set(policy::Policy::Unrestricted);
+        resumed.map(SignatureSha1)
+    }
+}
+
+#[test]
+fn it_returns_policy_violations_instead_of_rehashing() {
+    let data = data();
+    let path = log_file("policy");
+    fs::write(&path, &data[..1000]).unwrap();
+    let rehasher = AppendRehasher::new(&path);
+    rehasher.rehash::<SignatureSha1>().unwrap();
+    let err = rehasher.rehash::<SignatureSha1>().err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(path.with_extension("log.hashstate")).unwrap();
+}
@@ -0,0 +1,119 @@
+#![cfg(all(feature = "tokio", feature = "futures-io"))]
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures::FutureExt;
+use std::io;
+use streamsha::async_io::{AsyncHashingReader, AsyncHashingWriter};
+use streamsha::hash_state::HashState;
+use streamsha::traits::{Resumable, StreamHasher};
+use streamsha::policy::Purpose;
+use streamsha::*;
+
+fn data() -> Vec<u8> {
+    (0..100000).map(|i| (i * 13) as u8).collect()
+}
+
+#[tokio::test]
+async fn it_hashes_tokio_streams() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let data = data();
+
+    let mut reader = AsyncHashingReader::new(&data[..], Sha256::new());
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).await.unwrap();
+    assert_eq!(out, data);
+    assert_eq!(reader.finish(), sha256(&data));
+
+    let mut writer = AsyncHashingWriter::new(Vec::new(), Sha1::new());
+    writer.write_all(&data).await.unwrap();
+    writer.shutdown().await.unwrap();
+    let (out, hasher) = writer.into_parts();
+    assert_eq!(out, data);
+    assert_eq!(hasher.finish(), sha1(&data));
+
+    let mut hasher = Sha512::new();
+    tokio::io::copy(&mut &data[..], &mut hasher).await.unwrap();
+    assert_eq!(hasher.finish(), sha512(&data));
+}
+
+#[test]
+fn it_hashes_futures_streams() {
+    use futures::io::{AsyncReadExt, AsyncWriteExt};
+    let data = data();
+    futures::executor::block_on(async {
+        let mut reader = AsyncHashingReader::new(&data[..], Sha512::new());
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).await.unwrap();
+        assert_eq!(out, data);
+        assert_eq!(reader.finish(), sha512(&data));
+
+        let mut writer = AsyncHashingWriter::new(Vec::new(), Sha256::new());
+        writer.write_all(&data).await.unwrap();
+        writer.close().await.unwrap();
+        let (out, hasher) = writer.into_parts();
+        assert_eq!(out, data);
+        assert_eq!(hasher.finish(), sha256(&data));
+
+        let mut hasher = AnyHasher::with_purpose(Algorithm::Sha1, Purpose::KeyIdentifier).unwrap();
+        futures::io::copy(&data[..], &mut hasher).await.unwrap();
+        let mut out = [0u8; 20];
+        hasher.finish_into(&mut out).unwrap();
+        assert_eq!(out, sha1(&data));
+    });
+}
+
+/// Returns the first 1000 bytes, then never becomes ready again
+struct StallingReader<'a>(&'a [u8]);
+impl StallingReader<'_> {
+    fn poll(&mut self, buf: &mut [u8]) -> Poll<usize> {
+        let len = buf.len().min(self.0.len());
+        if len == 0 {
+            return Poll::Pending;
+        }
+        buf[..len].copy_from_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        Poll::Ready(len)
+    }
+}
+impl tokio::io::AsyncRead for StallingReader<'_> {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let mut tmp = vec![0u8; buf.remaining()];
+        self.get_mut().poll(&mut tmp).map(|len| {
+            buf.put_slice(&tmp[..len]);
+            Ok(())
+        })
+    }
+}
+impl futures::io::AsyncRead for StallingReader<'_> {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.get_mut().poll(buf).map(Ok)
+    }
+}
+
+fn message_len(state: &HashState) -> u64 {
+    match state {
+        HashState::Sha256(state) => state.message_len,
+        _ => panic!("unexpected hash state"),
+    }
+}
+
+#[test]
+fn it_hands_back_partial_state_on_cancellation() {
+    let data = data();
+
+    let mut reader = AsyncHashingReader::new(StallingReader(&data[..1000]), Sha256::new());
+    let mut out = Vec::new();
+    assert!(tokio::io::AsyncReadExt::read_to_end(&mut reader, &mut out).now_or_never().is_none());
+    let state = reader.pause();
+    assert_eq!(message_len(&state), 1000);
+
+    // resume from the persisted state with the rest of the upload
+    let mut hasher = Sha256::resume(state).unwrap();
+    hasher.update(&data[1000..]);
+    assert_eq!(hasher.finish(), sha256(&data));
+
+    let mut reader = AsyncHashingReader::new(StallingReader(&data[..1000]), Sha256::new());
+    let mut out = Vec::new();
+    assert!(futures::io::AsyncReadExt::read_to_end(&mut reader, &mut out).now_or_never().is_none());
+    assert_eq!(message_len(&reader.pause()), 1000);
+}
@@ -0,0 +1,97 @@
+use core::hash::{BuildHasher, Hasher};
+use std::collections::HashMap;
+use streamsha::build_hasher::StreamshaHasher;
+use streamsha::md::{Endianness, MdCompression, MdHasher};
+use streamsha::traits::StreamHasher;
+use streamsha::*;
+
+struct XorCompression;
+
+impl MdCompression for XorCompression {
+    type Word = u32;
+    type State = [u32; 1];
+    type Block = [u8; 64];
+    type Output = [u8; 4];
+    const ID: u64 = 0x584f_5200_0000;
+    const IV: [u32; 1] = [0];
+    const EMPTY_BLOCK: [u8; 64] = [0; 64];
+    const BLOCK_SIZE: usize = 64;
+    const LENGTH_SIZE: usize = 8;
+    const ENDIANNESS: Endianness = Endianness::Big;
+    const MAX_MESSAGE_LEN: u128 = u64::MAX as u128 / 8;
+    fn compress(state: &mut [u32; 1], blocks: &[u8]) {
+        for word in blocks.chunks(4) {
+            state[0] ^= u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+    }
+    fn output(state: &[u32; 1]) -> [u8; 4] {
+        state[0].to_be_bytes()
+    }
+}
+
+fn hash_bytes<B: BuildHasher>(build_hasher: &B, data: &[u8]) -> u64 {
+    let mut hasher = build_hasher.build_hasher();
+    hasher.write(data);
+    hasher.finish()
+}
+
+#[test]
+fn it_truncates_plain_digest() {
+    let build_hasher = StreamshaBuildHasher::<Sha256Compression>::new();
+    assert_eq!(hash_bytes(&build_hasher, b"abc"), 0xba7816bf8f01cfea);
+    let build_hasher = StreamshaBuildHasher::<Sha512Compression>::default();
+    assert_eq!(hash_bytes(&build_hasher, b"abc"), 0xddaf35a193617aba);
+}
+
+#[test]
+fn it_zero_pads_short_digests() {
+    let mut hasher = MdHasher::<XorCompression>::new();
+    hasher.update(b"abcd");
+    let digest = u32::from_be_bytes(hasher.finish());
+    match StreamshaBuildHasher::<XorCompression>::try_new() {
+        Ok(build_hasher) => assert_eq!(hash_bytes(&build_hasher, b"abcd"), u64::from(digest) << 32),
+        // custom hashes are forbidden under the `FipsApproved` floor
+        Err(_) => assert_eq!(policy::current(), policy::Policy::FipsApproved),
+    }
+    if let Ok(build_hasher) = StreamshaBuildHasher::<XorCompression>::try_with_key(b"key") {
+        assert_eq!(build_hasher.hash_one(1u8) & 0xffff_ffff, 0);
+    }
+}
+
+#[test]
+fn it_keys_with_hmac() {
+    // RFC 4231 test case 2
+    let build_hasher = StreamshaBuildHasher::<Sha256Compression>::with_key(b"Jefe");
+    assert_eq!(hash_bytes(&build_hasher, b"what do ya want for nothing?"), 0x5bdcc146bf60754e);
+    let build_hasher = StreamshaBuildHasher::<Sha512Compression>::with_key(b"Jefe");
+    assert_eq!(hash_bytes(&build_hasher, b"what do ya want for nothing?"), 0x164b7a7bfcf819e2);
+
+    // RFC 4231 test case 6, with a key longer than the block
+    let build_hasher = StreamshaBuildHasher::<Sha256Compression>::with_key(&[0xaa; 131]);
+    let data = b"Test Using Larger Than Block-Size Key - Hash Key First";
+    assert_eq!(hash_bytes(&build_hasher, data), 0x60e431591ee0b67f);
+}
+
+#[test]
+fn it_hashes_integers_independent_of_platform() {
+    let build_hasher = StreamshaBuildHasher::<Sha256Compression>::new();
+    let mut expected: StreamshaHasher = build_hasher.build_hasher();
+    expected.write(&[1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0xfd]);
+    assert_eq!(build_hasher.hash_one((1usize, 2u32, -3i8)), expected.finish());
+}
+
+#[test]
+fn it_can_be_used_in_maps() {
+    let build_hasher = StreamshaBuildHasher::<Sha256Compression>::with_key(b"shard key");
+    let mut map = HashMap::with_hasher(build_hasher.clone());
+    for i in 0..1000u32 {
+        map.insert(i, i * 2);
+    }
+    for i in 0..1000u32 {
+        assert_eq!(map[&i], i * 2);
+    }
+    assert_ne!(
+        build_hasher.hash_one("abc"),
+        StreamshaBuildHasher::<Sha256Compression>::new().hash_one("abc")
+    );
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "std")]
+use std::fs;
+use std::io::{self, Cursor, Read};
+use std::path::PathBuf;
+use streamsha::checkpoint::{resume_from_checkpoint, Checkpoint, CheckpointingHasher};
+use streamsha::*;
+
+fn data() -> Vec<u8> {
+    (0..100000).map(|i| (i * 13) as u8).collect()
+}
+
+fn sidecar(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("streamsha-{}-{}.ckpt", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn it_resumes_after_restart() {
+    let data = data();
+    let path = sidecar("resume");
+    {
+        let mut hasher = CheckpointingHasher::new(Sha256::new(), &path).with_interval(10000);
+        // crash after 45678 bytes
+        io::copy(&mut Cursor::new(&data).take(45678), &mut hasher).unwrap();
+    }
+    let checkpoint = Checkpoint::read(&path).unwrap();
+    assert!(checkpoint.offset >= 40000 && checkpoint.offset <= 45678);
+
+    let mut reader = Cursor::new(&data);
+    let mut hasher = resume_from_checkpoint::<Sha256, _>(&path, &mut reader).unwrap();
+    assert_eq!(hasher.offset(), checkpoint.offset);
+    assert_eq!(reader.position(), checkpoint.offset);
+    io::copy(&mut reader, &mut hasher).unwrap();
+    assert_eq!(hasher.offset(), data.len() as u64);
+    assert_eq!(hasher.finish().unwrap(), sha256(&data));
+    assert!(!path.exists());
+}
+
+#[test]
+fn it_checkpoints_on_demand() {
+    let data = data();
+    let path = sidecar("on-demand");
+    let mut hasher = CheckpointingHasher::new(Sha512::new(), &path);
+    hasher.update(&data[..1234]).unwrap();
+    assert!(!path.exists());
+    hasher.checkpoint().unwrap();
+    let mut reader = Cursor::new(&data);
+    let mut hasher = resume_from_checkpoint::<Sha512, _>(&path, &mut reader).unwrap();
+    hasher.update(&data[1234..]).unwrap();
+    assert_eq!(hasher.finish().unwrap(), sha512(&data));
+}
+
+#[test]
+fn it_rejects_invalid_checkpoints() {
+    let path = sidecar("invalid");
+    let mut reader = Cursor::new(data());
+    let err = resume_from_checkpoint::<Sha256, _>(&path, &mut reader).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    let mut hasher = CheckpointingHasher::new(Sha256::new(), &path);
+    hasher.update(b"abc").unwrap();
+    hasher.checkpoint().unwrap();
+    let err = resume_from_checkpoint::<Sha1, _>(&path, &mut reader).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[10] ^= 1;
+    fs::write(&path, &bytes).unwrap();
+    let err = resume_from_checkpoint::<Sha256, _>(&path, &mut reader).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn it_does_not_hash_data_when_checkpointing_fails() {
+    let data = data();
+    let dir = std::env::temp_dir().join(format!("streamsha-unwritable-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let path = dir.join("sidecar.ckpt");
+    let mut hasher = CheckpointingHasher::new(Sha256::new(), &path).with_interval(1000);
+    // the directory of the sidecar doesn't exist, so only the bytes before the first checkpoint are hashed
+    assert_eq!(hasher.update(&data[..1500]).unwrap(), 1000);
+    assert!(hasher.update(&data[1000..1500]).is_err());
+    assert_eq!(hasher.offset(), 1000);
+    fs::create_dir(&dir).unwrap();
+    assert_eq!(hasher.update(&data[1000..2000]).unwrap(), 1000);
+    assert_eq!(Checkpoint::read(&path).unwrap().offset, 1000);
+    hasher.update(&data[2000..]).unwrap();
+    assert_eq!(hasher.finish().unwrap(), sha256(&data));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,92 @@
+use hex_literal::hex;
+use streamsha::traits::StreamHasher;
+use streamsha::*;
+
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+#[test]
+fn it_can_compress_padded_block() {
+    // "abc" padded by hand
+    let mut block = [0u8; 64];
+    block[..3].copy_from_slice(b"abc");
+    block[3] = 0x80;
+    block[63] = 24;
+    let mut state = SHA256_IV;
+    compress256(&mut state, &block);
+    let digest = hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    for (word, bytes) in state.iter().zip(digest.chunks(4)) {
+        assert_eq!(word.to_be_bytes(), bytes);
+    }
+}
+
+#[test]
+fn it_can_continue_from_midstate() {
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+
+    let mut hasher = Sha1::new();
+    hasher.update(&data[..128]);
+    let h = hasher.finish_unpadded();
+    let mut hasher = Sha1::with_iv(h, 128);
+    hasher.update(&data[128..]);
+    assert_eq!(hasher.finish(), streamsha::sha1(&data));
+
+    let mut hasher = Sha256::new();
+    hasher.update(&data[..128]);
+    let h = hasher.finish_unpadded();
+    let mut hasher = Sha256::with_iv(h, 128);
+    hasher.update(&data[128..]);
+    assert_eq!(hasher.finish(), streamsha::sha256(&data));
+
+    let mut hasher = Sha512::new();
+    hasher.update(&data[..256]);
+    let h = hasher.finish_unpadded();
+    let mut hasher = Sha512::with_iv(h, 256);
+    hasher.update(&data[256..]);
+    assert_eq!(hasher.finish(), streamsha::sha512(&data));
+}
+
+#[test]
+fn it_matches_hasher_midstate() {
+    let data: Vec<u8> = (0..512).map(|i| (i * 3) as u8).collect();
+
+    let mut state = Sha1::new().finish_unpadded();
+    for block in data.as_chunks::<64>().0 {
+        compress_sha1(&mut state, block);
+    }
+    let mut hasher = Sha1::new();
+    hasher.update(&data);
+    assert_eq!(hasher.finish_unpadded(), state);
+
+    let mut state = Sha256::new().finish_unpadded();
+    assert_eq!(state, SHA256_IV);
+    for block in data.as_chunks::<64>().0 {
+        compress256(&mut state, block);
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(&data);
+    assert_eq!(hasher.finish_unpadded(), state);
+
+    let mut state = Sha512::new().finish_unpadded();
+    for block in data.as_chunks::<128>().0 {
+        compress512(&mut state, block);
+    }
+    let mut hasher = Sha512::new();
+    hasher.update(&data);
+    assert_eq!(hasher.finish_unpadded(), state);
+}
+
+#[test]
+#[should_panic(expected = "block is not filled")]
+fn it_refuses_to_finish_unpadded_with_pending_bytes() {
+    let mut hasher = Sha256::new();
+    hasher.update(b"abc");
+    hasher.finish_unpadded();
+}
+
+#[test]
+#[should_panic(expected = "invalid message length")]
+fn it_rejects_unaligned_message_len() {
+    Sha256::with_iv(SHA256_IV, 3);
+}
@@ -0,0 +1,109 @@
+use streamsha::hash_state::{self, HashState};
+use streamsha::traits::Resumable;
+use streamsha::dynamic::DynHasher;
+use streamsha::output::BufferTooSmall;
+use streamsha::policy::Purpose;
+use streamsha::*;
+
+#[test]
+fn it_maps_names_and_oids() {
+    for &algorithm in Algorithm::ALL.iter() {
+        assert_eq!(Algorithm::from_name(algorithm.name()), Some(algorithm));
+        assert_eq!(Algorithm::from_oid(algorithm.oid()), Some(algorithm));
+        assert_eq!(Algorithm::from_oid_der(algorithm.oid_der()), Some(algorithm));
+        assert_eq!(Algorithm::from_hash_state(&algorithm.initial_state()), Some(algorithm));
+    }
+    assert_eq!(Algorithm::from_name("sha256"), Some(Algorithm::Sha256));
+    assert_eq!(Algorithm::from_name("Sha-1"), Some(Algorithm::Sha1));
+    assert_eq!(Algorithm::from_name("SHA512"), Some(Algorithm::Sha512));
+    assert_eq!(Algorithm::from_name("SHA-384"), None);
+    assert_eq!(Algorithm::from_name("sha256sha256sha256"), None);
+    assert_eq!(Algorithm::from_oid("2.16.840.1.101.3.4.2.1"), Some(Algorithm::Sha256));
+    assert_eq!(Algorithm::from_oid("2.16.840.1.101.3.4.2.2"), None);
+    assert_eq!(Algorithm::Sha256.to_string(), "SHA-256");
+}
+
+#[test]
+fn it_hashes_with_runtime_algorithm() {
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+    let expected: [&[u8]; 3] = [&sha1(&data), &sha256(&data), &sha512(&data)];
+    for (&algorithm, expected) in Algorithm::ALL.iter().zip(expected.iter()) {
+        // SHA-1 is allowed for key identifiers under every policy
+        let mut hasher = AnyHasher::with_purpose(algorithm, Purpose::KeyIdentifier).unwrap();
+        hasher.update(&data[..300]);
+        let state = hasher.pause();
+        hasher.update(&data[300..]);
+        let mut out = [0u8; Algorithm::MAX_OUTPUT_SIZE];
+        assert_eq!(hasher.finish_into(&mut out), Ok(algorithm.output_size()));
+        assert_eq!(&out[..algorithm.output_size()], *expected);
+
+        // resumed from the state paused in the middle
+        let mut hasher = AnyHasher::resume_with_purpose(state, Purpose::KeyIdentifier).unwrap();
+        assert_eq!(hasher.algorithm(), algorithm);
+        hasher.update(&data[300..]);
+        hasher.finish_into(&mut out).unwrap();
+        assert_eq!(&out[..algorithm.output_size()], *expected);
+    }
+}
+
+#[test]
+fn it_resets_after_finish_into() {
+    let mut hasher = AnyHasher::new(Algorithm::Sha256).unwrap();
+    let mut out = [0u8; 32];
+    hasher.update(b"garbage");
+    hasher.finish_into(&mut out).unwrap();
+    hasher.update(b"abc");
+    hasher.finish_into(&mut out).unwrap();
+    assert_eq!(out, sha256(b"abc"));
+}
+
+#[test]
+fn it_can_be_used_as_trait_object() {
+    fn hash(hasher: &mut dyn DynHasher, data: &[u8], out: &mut [u8]) -> usize {
+        hasher.update(data);
+        hasher.finish_into(out).unwrap()
+    }
+    let mut out = [0u8; 64];
+    assert_eq!(hash(&mut AnyHasher::with_purpose(Algorithm::Sha1, Purpose::KeyIdentifier).unwrap(), b"abc", &mut out), 20);
+    assert_eq!(out[..20], sha1(b"abc"));
+    let mut hasher = Sha512::new();
+    assert_eq!(hash(&mut hasher, b"abc", &mut out), 64);
+    assert_eq!(out, sha512(b"abc"));
+    // reset by finish_into
+    let hasher: &mut dyn DynHasher = &mut hasher;
+    assert_eq!(hasher.output_size(), 64);
+    assert!(matches!(hasher.pause(), HashState::Sha512(ref hs) if hs.message_len == 0));
+}
+
+#[test]
+fn it_rejects_short_output() {
+    let mut hasher = AnyHasher::new(Algorithm::Sha512).unwrap();
+    hasher.update(b"abc");
+    assert_eq!(hasher.finish_into(&mut [0u8; 63]), Err(BufferTooSmall));
+    // the running state is kept
+    let mut out = [0u8; 64];
+    assert_eq!(hasher.finish_into(&mut out), Ok(64));
+    assert_eq!(out, sha512(b"abc"));
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"abc");
+    let hasher: &mut dyn DynHasher = &mut hasher;
+    assert_eq!(hasher.output_size(), 32);
+    assert_eq!(hasher.finish_into(&mut out[..31]), Err(BufferTooSmall));
+    assert_eq!(hasher.finish_into(&mut out), Ok(32));
+    assert_eq!(out[..32], sha256(b"abc"));
+}
+
+#[test]
+fn it_rejects_generic_hash_state() {
+    let state = Sha256::new().pause();
+    assert!(AnyHasher::resume(state).is_ok());
+    let state = HashState::Md(hash_state::MdHashState {
+        id: 0x10000,
+        h: [0; 128],
+        message_len: 0,
+        block_len: 0,
+        current_block: [0; 256],
+    });
+    assert!(matches!(AnyHasher::resume(state), Err(hash_state::Error::HashTypeNotMatch)));
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "embedded-io")]
+use embedded_io::{Read, Write};
+use streamsha::embedded::HashingReader;
+use streamsha::traits::{Resumable, StreamHasher};
+use streamsha::*;
+
+fn data() -> Vec<u8> {
+    (0..10000).map(|i| (i * 13) as u8).collect()
+}
+
+#[test]
+fn it_can_write_into_hasher() {
+    let data = data();
+    let mut hasher = Sha1::new();
+    hasher.write_all(&data).unwrap();
+    hasher.flush().unwrap();
+    assert_eq!(hasher.finish(), sha1(&data));
+
+    let mut hasher = AnyHasher::new(Algorithm::Sha512).unwrap();
+    Write::write_all(&mut hasher, &data).unwrap();
+    let mut out = [0u8; 64];
+    hasher.finish_into(&mut out).unwrap();
+    assert_eq!(out, sha512(&data));
+}
+
+#[test]
+fn it_tees_reads_into_hasher() {
+    let data = data();
+    let mut reader = HashingReader::new(&data[..], Sha256::new());
+    // small buffer, as on a card reader
+    let mut buf = [0u8; 261];
+    let mut read = 0;
+    loop {
+        let len = reader.read(&mut buf).unwrap();
+        if len == 0 {
+            break;
+        }
+        assert_eq!(buf[..len], data[read..read + len]);
+        read += len;
+    }
+    assert_eq!(read, data.len());
+    assert_eq!(reader.finish(), sha256(&data));
+}
+
+#[test]
+fn it_can_pause_tee() {
+    let data = data();
+    let mut reader = HashingReader::new(&data[..], Sha256::new());
+    reader.read_exact(&mut [0u8; 1000]).unwrap();
+    let mut hasher = Sha256::resume(reader.pause()).unwrap();
+    hasher.update(&data[1000..]);
+    assert_eq!(hasher.finish(), sha256(&data));
+}
+
+#[cfg(feature = "embedded-io-async")]
+#[test]
+fn it_supports_async_traits() {
+    use embedded_io_async::{Read as AsyncRead, Write as AsyncWrite};
+    let data = data();
+    futures::executor::block_on(async {
+        let mut hasher = Sha512::new();
+        AsyncWrite::write_all(&mut hasher, &data).await.unwrap();
+        assert_eq!(hasher.finish(), sha512(&data));
+
+        let mut reader = HashingReader::new(&data[..], Sha1::new());
+        let mut buf = [0u8; 64];
+        while AsyncRead::read(&mut reader, &mut buf).await.unwrap() != 0 {}
+        assert_eq!(reader.finish(), sha1(&data));
+    });
+}
@@ -0,0 +1,162 @@
+use streamsha::hash_state::{self, HashState, MdHashState, Sha256HashState};
+use streamsha::md::{Endianness, MdCompression, MdHasher};
+use streamsha::policy::Purpose;
+use streamsha::traits::{Resumable, StreamHasher};
+use streamsha::*;
+
+/// A toy compression function with 64-byte blocks
+struct XorCompression;
+
+impl MdCompression for XorCompression {
+    type Word = u32;
+    type State = [u32; 1];
+    type Block = [u8; 64];
+    type Output = [u8; 4];
+    const ID: u64 = 0x584f_5200_0000;
+    const IV: [u32; 1] = [0];
+    const EMPTY_BLOCK: [u8; 64] = [0; 64];
+    const BLOCK_SIZE: usize = 64;
+    const LENGTH_SIZE: usize = 8;
+    const ENDIANNESS: Endianness = Endianness::Big;
+    const MAX_MESSAGE_LEN: u128 = u64::MAX as u128 / 8;
+    fn compress(state: &mut [u32; 1], blocks: &[u8]) {
+        for word in blocks.chunks(4) {
+            state[0] ^= u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+    }
+    fn output(state: &[u32; 1]) -> [u8; 4] {
+        state[0].to_be_bytes()
+    }
+}
+
+fn data() -> Vec<u8> {
+    (0..1000).map(|i| (i * 13) as u8).collect()
+}
+
+fn encode(state: &HashState) -> Vec<u8> {
+    let mut buf = [0u8; HashState::MAX_ENCODED_LEN];
+    let len = state.encode(&mut buf).unwrap();
+    buf[..len].to_vec()
+}
+
+fn states(data: &[u8]) -> Vec<HashState> {
+    let mut sha1 = Sha1::new();
+    sha1.update(data);
+    let mut sha256 = Sha256::new();
+    sha256.update(data);
+    let mut sha512 = Sha512::new();
+    sha512.update(data);
+    let mut multi = MultiHasher::with_purpose(&[Algorithm::Sha1, Algorithm::Sha512], Purpose::KeyIdentifier).unwrap();
+    multi.update(data);
+    vec![sha1.pause(), sha256.pause(), sha512.pause(), multi.pause()]
+}
+
+#[test]
+fn it_round_trips_encoded_states() {
+    let data = data();
+    for len in [0, 1, 63, 64, 127, 128, 999].iter().copied() {
+        for state in states(&data[..len]) {
+            let bytes = encode(&state);
+            assert!(bytes.len() <= HashState::MAX_ENCODED_LEN);
+            assert_eq!(&bytes[..5], b"SSHS\x01");
+            let decoded = HashState::decode(&bytes).unwrap();
+            assert_eq!(encode(&decoded), bytes);
+        }
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(&data[..333]);
+    let mut hasher = Sha256::resume(HashState::decode(&encode(&hasher.pause())).unwrap()).unwrap();
+    hasher.update(&data[333..]);
+    assert_eq!(hasher.finish(), sha256(&data));
+}
+
+#[test]
+fn it_has_stable_encoding() {
+    let mut hasher = Sha1::new();
+    hasher.update(b"abc");
+    let mut expected = b"SSHS\x01\x01".to_vec();
+    for word in [0x67452301u32, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0].iter() {
+        expected.extend_from_slice(&word.to_be_bytes());
+    }
+    expected.extend_from_slice(&3u64.to_be_bytes());
+    expected.extend_from_slice(&[0, 3, b'a', b'b', b'c']);
+    assert_eq!(encode(&hasher.pause()), expected);
+}
+
+#[test]
+fn it_encodes_md_states() {
+    let mut buf = [0u8; HashState::MAX_ENCODED_LEN];
+    let mut md = MdHashState {
+        id: 0x1234_0000,
+        h: [7; 128],
+        message_len: 100,
+        block_len: 100,
+        current_block: [9; 256],
+    };
+    md.current_block[100..].fill(0);
+    let len = HashState::Md(md).encode(&mut buf).unwrap();
+    match HashState::decode(&buf[..len]).unwrap() {
+        HashState::Md(hs) => {
+            assert_eq!(hs.id, 0x1234_0000);
+            assert_eq!(hs.h, [7; 128]);
+            assert_eq!(hs.block_len, 100);
+            assert_eq!(hs.current_block[..100], [9; 100][..]);
+        }
+        _ => panic!("unexpected hash state"),
+    }
+}
+
+#[test]
+fn it_rejects_invalid_encoding() {
+    let bytes = encode(&states(b"abc").remove(1));
+    let invalid = |bytes: &[u8]| matches!(HashState::decode(bytes), Err(hash_state::Error::InvalidEncoding));
+    assert!(invalid(&bytes[..bytes.len() - 1]));
+    assert!(invalid(&[&bytes[..], &[0]].concat()));
+    assert!(invalid(b"SSHS\x02"));
+    let mut wrong_tag = bytes.clone();
+    wrong_tag[5] = 9;
+    assert!(invalid(&wrong_tag));
+    let mut long_block = bytes;
+    let block_len = long_block.len() - 5;
+    long_block[block_len] = 0x01; // block_len 0x0103 > block size
+    assert!(invalid(&long_block));
+
+    let mut buf = [0u8; 10];
+    assert!(matches!(Algorithm::Sha1.initial_state().encode(&mut buf), Err(hash_state::Error::BufferTooSmall)));
+}
+
+#[test]
+fn it_refuses_to_encode_oversized_block_len() {
+    let mut buf = [0u8; HashState::MAX_ENCODED_LEN];
+    let md = HashState::Md(MdHashState {
+        id: XorCompression::ID,
+        h: [0; 128],
+        message_len: 256,
+        block_len: 256,
+        current_block: [0; 256],
+    });
+    assert!(matches!(md.encode(&mut buf), Err(hash_state::Error::InvalidState)));
+    let sha256 = HashState::Sha256(Sha256HashState {
+        h: [0; 8],
+        message_len: 1000,
+        block_len: 1000,
+        current_block: [0; 64],
+    });
+    assert!(matches!(sha256.encode(&mut buf), Err(hash_state::Error::InvalidState)));
+}
+
+#[test]
+fn it_rejects_decoded_md_states_with_oversized_block_len() {
+    // decodes, as the encoding only bounds `block_len` by `MD_MAX_BLOCK_SIZE`
+    let mut current_block = [0; 256];
+    current_block[..100].fill(9);
+    let state = HashState::Md(MdHashState {
+        id: XorCompression::ID,
+        h: [0; 128],
+        message_len: 100,
+        block_len: 100,
+        current_block,
+    });
+    let decoded = HashState::decode(&encode(&state)).unwrap();
+    assert!(matches!(MdHasher::<XorCompression>::resume(decoded), Err(hash_state::Error::InvalidState)));
+}
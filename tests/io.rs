@@ -0,0 +1,104 @@
+#![cfg(feature = "std")]
+use std::io::{self, IoSlice, Read, Write};
+use streamsha::io::{hash_reader, hash_reader_with, HashingReader, HashingWriter};
+use streamsha::traits::{Resumable, StreamHasher};
+use streamsha::policy::Purpose;
+use streamsha::*;
+
+fn data() -> Vec<u8> {
+    (0..100000).map(|i| (i * 13) as u8).collect()
+}
+
+#[test]
+fn it_can_copy_into_hasher() {
+    let data = data();
+    let mut hasher = Sha512::new();
+    io::copy(&mut &data[..], &mut hasher).unwrap();
+    assert_eq!(hasher.finish(), sha512(&data));
+
+    let mut hasher = AnyHasher::with_purpose(Algorithm::Sha1, Purpose::KeyIdentifier).unwrap();
+    io::copy(&mut &data[..], &mut hasher).unwrap();
+    let mut out = [0u8; 20];
+    hasher.finish_into(&mut out).unwrap();
+    assert_eq!(out, sha1(&data));
+}
+
+#[test]
+fn it_can_write_vectored_into_hasher() {
+    let data = data();
+    let bufs: Vec<IoSlice<'_>> = data.chunks(1000).map(IoSlice::new).collect();
+    let mut hasher = Sha1::new();
+    assert_eq!(hasher.write_vectored(&bufs).unwrap(), data.len());
+    assert_eq!(hasher.finish(), sha1(&data));
+
+    let mut hasher = AnyHasher::new(Algorithm::Sha256).unwrap();
+    assert_eq!(hasher.write_vectored(&bufs[..3]).unwrap(), 3000);
+    let mut out = [0u8; 32];
+    hasher.finish_into(&mut out).unwrap();
+    assert_eq!(out, sha256(&data[..3000]));
+}
+
+#[test]
+fn it_hashes_while_reading() {
+    let data = data();
+    let mut reader = HashingReader::new(&data[..], Sha256::new());
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, data);
+    assert_eq!(reader.finish(), sha256(&data));
+}
+
+/// Accepts at most 100 bytes per write
+struct SlowWriter(Vec<u8>);
+impl Write for SlowWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(100);
+        self.0.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn it_hashes_only_written_bytes() {
+    let data = data();
+    let mut writer = HashingWriter::new(SlowWriter(Vec::new()), Sha256::new());
+    assert_eq!(writer.write(&data).unwrap(), 100);
+    writer.write_all(&data[100..]).unwrap();
+    let (inner, hasher) = writer.into_parts();
+    assert_eq!(inner.0, data);
+    assert_eq!(hasher.finish(), sha256(&data));
+}
+
+/// Fails once after `fail_at` bytes
+struct FlakyReader<'a> {
+    data: &'a [u8],
+    fail_at: usize,
+}
+impl Read for FlakyReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.fail_at == 0 {
+            self.fail_at = usize::MAX;
+            return Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+        }
+        let len = buf.len().min(self.data.len()).min(self.fail_at);
+        buf[..len].copy_from_slice(&self.data[..len]);
+        self.data = &self.data[len..];
+        self.fail_at -= len;
+        Ok(len)
+    }
+}
+
+#[test]
+fn it_can_resume_interrupted_reading() {
+    let data = data();
+    assert_eq!(hash_reader::<Sha256, _>(&data[..]).unwrap(), sha256(&data));
+
+    let mut reader = FlakyReader { data: &data, fail_at: 12345 };
+    let interrupted = hash_reader::<Sha256, _>(&mut reader).unwrap_err();
+    assert_eq!(interrupted.error.kind(), io::ErrorKind::ConnectionReset);
+    let hasher = Sha256::resume(*interrupted.state).unwrap();
+    assert_eq!(hash_reader_with(hasher, &mut reader).unwrap(), sha256(&data));
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "length-extension")]
+use streamsha::length_extension::LengthExtension;
+use streamsha::*;
+
+fn check_forgery<H: LengthExtension + Default>(mac: impl Fn(&[u8]) -> H::Output)
+where
+    H::Output: PartialEq + core::fmt::Debug,
+{
+    let key = b"0123456789abcdef0123456789";
+    let suffix = b";admin=true";
+    for msg_len in 0..300 {
+        let message: Vec<u8> = (0..msg_len).map(|i| i as u8).collect();
+        let tag = mac(&[&key[..], &message].concat());
+        let forgery = H::extend(&tag, (key.len() + message.len()) as u64, suffix);
+        let forged_message = [&key[..], &message, forgery.glue.as_bytes(), suffix].concat();
+        assert_eq!(mac(&forged_message), forgery.digest, "msg_len = {}", msg_len);
+    }
+}
+
+#[test]
+fn it_forges_sha1_mac() {
+    check_forgery::<Sha1>(streamsha::sha1);
+}
+
+#[test]
+fn it_forges_sha256_mac() {
+    check_forgery::<Sha256>(streamsha::sha256);
+}
+
+#[test]
+fn it_forges_sha512_mac() {
+    check_forgery::<Sha512>(streamsha::sha512);
+}
+
+#[test]
+fn it_computes_glue_padding_as_finish() {
+    let glue = Sha256::glue_padding(3);
+    let mut expected = vec![0x80];
+    expected.extend_from_slice(&[0; 52]);
+    expected.extend_from_slice(&24u64.to_be_bytes());
+    assert_eq!(glue.as_bytes(), &expected[..]);
+    // the padded message is a whole number of blocks
+    for len in 0..300u64 {
+        assert_eq!((len + Sha1::glue_padding(len).as_bytes().len() as u64) % 64, 0);
+        assert_eq!((len + Sha256::glue_padding(len).as_bytes().len() as u64) % 64, 0);
+        assert_eq!((len + Sha512::glue_padding(len).as_bytes().len() as u64) % 128, 0);
+    }
+}
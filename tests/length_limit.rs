@@ -0,0 +1,56 @@
+use streamsha::hash_state::MdHashState;
+use streamsha::md::{MdCompression, MdHasher};
+use streamsha::policy::Purpose;
+use streamsha::traits::{LengthLimitExceeded, StreamHasher};
+use streamsha::{Sha1Compression, Sha256Compression, Sha512Compression};
+
+/// A hasher that accepts 2 more bytes
+fn hasher_near_length_limit<C: MdCompression>() -> MdHasher<C> {
+    let message_len = C::MAX_MESSAGE_LEN - 2;
+    // SHA-1 is allowed for key identifiers under every policy
+    let state = C::into_hash_state(MdHashState {
+        id: C::ID,
+        h: [0; 128],
+        message_len,
+        block_len: (message_len % C::BLOCK_SIZE as u128) as usize,
+        current_block: [0; 256],
+    });
+    MdHasher::resume_with_purpose(state, Purpose::KeyIdentifier).unwrap()
+}
+
+macro_rules! length_limit_tests {
+    ($name:ident, $compression:ty) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn it_rejects_too_long_message() {
+                let mut hasher = hasher_near_length_limit::<$compression>();
+                assert_eq!(hasher.try_update(&[0; 3]), Err(LengthLimitExceeded));
+                assert_eq!(hasher.try_update(&[0; 2]), Ok(2));
+                assert_eq!(hasher.try_update(&[0]), Err(LengthLimitExceeded));
+                hasher.finish();
+            }
+            #[test]
+            fn it_fails_to_try_finish_overflowed_message() {
+                let mut hasher = hasher_near_length_limit::<$compression>();
+                hasher.update(&[0; 2]);
+                assert!(hasher.clone().try_finish().is_ok());
+                hasher.update(&[0; 1]);
+                assert_eq!(hasher.try_finish(), Err(LengthLimitExceeded));
+            }
+            #[test]
+            #[should_panic(expected = "message length limit exceeded")]
+            fn it_refuses_to_finish_overflowed_message() {
+                let mut hasher = hasher_near_length_limit::<$compression>();
+                hasher.update(&[0; 3]);
+                hasher.update(&[0; 1000]);
+                hasher.finish();
+            }
+        }
+    };
+}
+
+length_limit_tests!(sha1, Sha1Compression);
+length_limit_tests!(sha256, Sha256Compression);
+length_limit_tests!(sha512, Sha512Compression);
@@ -0,0 +1,158 @@
+use hex_literal::hex;
+use streamsha::hash_state::{self, HashState};
+use streamsha::md::{store_words, Endianness, MdCompression, MdHasher};
+use streamsha::traits::{Resumable, StreamHasher};
+
+/// MD5, as an MD-style hash with little endian words and length field
+struct Md5Compression;
+impl MdCompression for Md5Compression {
+    type Word = u32;
+    type State = [u32; 4];
+    type Block = [u8; 64];
+    type Output = [u8; 16];
+    const ID: u64 = 0x4d44_3500_0000;
+    const IV: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    const EMPTY_BLOCK: [u8; 64] = [0; 64];
+    const BLOCK_SIZE: usize = 64;
+    const LENGTH_SIZE: usize = 8;
+    const ENDIANNESS: Endianness = Endianness::Little;
+    const MAX_MESSAGE_LEN: u128 = u64::MAX as u128 / 8;
+    fn compress(state: &mut [u32; 4], blocks: &[u8]) {
+        const S: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+        for block in blocks.chunks(64) {
+            let mut m = [0u32; 16];
+            for (word, bytes) in m.iter_mut().zip(block.chunks(4)) {
+                *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+            let [mut a, mut b, mut c, mut d] = *state;
+            for i in 0..64 {
+                let (f, g) = match i / 16 {
+                    0 => ((b & c) | (!b & d), i),
+                    1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                    2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                    _ => (c ^ (b | !d), (7 * i) % 16),
+                };
+                let k = ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32;
+                let f = f.wrapping_add(a).wrapping_add(k).wrapping_add(m[g]);
+                a = d;
+                d = c;
+                c = b;
+                b = b.wrapping_add(f.rotate_left(S[(i / 16) * 4 + i % 4]));
+            }
+            for (word, x) in state.iter_mut().zip([a, b, c, d]) {
+                *word = word.wrapping_add(x);
+            }
+        }
+    }
+    fn output(state: &[u32; 4]) -> [u8; 16] {
+        let mut final_hash = [0u8; 16];
+        store_words(state, Self::ENDIANNESS, &mut final_hash);
+        final_hash
+    }
+}
+type Md5 = MdHasher<Md5Compression>;
+
+#[allow(non_upper_case_globals)]
+const vectors: &[(&[u8], [u8; 16])] = &[
+    (b"", hex!("d41d8cd98f00b204e9800998ecf8427e")),
+    (b"abc", hex!("900150983cd24fb0d6963f7d28e17f72")),
+    (b"message digest", hex!("f96b697d7cb7938d525a2f31aaf161d0")),
+    (
+        b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+        hex!("57edf4a22be3c955ac49da2e2107b67a"),
+    ),
+];
+
+#[test]
+fn it_can_hash_with_custom_compression() {
+    for (input, expected) in vectors {
+        let mut hasher = Md5::new();
+        hasher.update(input);
+        assert_eq!(hasher.finish(), *expected);
+    }
+}
+
+#[test]
+fn it_can_pause_custom_hasher() {
+    let data = vectors[3].0;
+    for i in 0..data.len() {
+        let mut hasher = Md5::new();
+        hasher.update(&data[..i]);
+        let state = hasher.pause();
+        assert!(matches!(state, HashState::Md(ref hs) if hs.id == Md5Compression::ID));
+        let mut hasher = Md5::resume(state).unwrap();
+        hasher.update(&data[i..]);
+        assert_eq!(hasher.finish(), vectors[3].1);
+    }
+}
+
+#[test]
+fn it_rejects_other_hash_state() {
+    let state = streamsha::Sha256::new().pause();
+    assert!(matches!(Md5::resume(state), Err(hash_state::Error::HashTypeNotMatch)));
+    let state = Md5::new().pause();
+    assert!(matches!(streamsha::Sha256::resume(state), Err(hash_state::Error::HashTypeNotMatch)));
+}
+
+#[test]
+fn it_rejects_inconsistent_block_len() {
+    let state = |message_len: u128, block_len: usize| {
+        let mut hasher = Md5::new();
+        hasher.update(&[0; 10]);
+        match hasher.pause() {
+            HashState::Md(mut hs) => {
+                hs.message_len = message_len;
+                hs.block_len = block_len;
+                HashState::Md(hs)
+            }
+            _ => unreachable!(),
+        }
+    };
+    assert!(Md5::resume(state(10, 10)).is_ok());
+    assert!(Md5::resume(state(64 + 10, 10)).is_ok());
+    assert!(matches!(Md5::resume(state(10, 11)), Err(hash_state::Error::InvalidState)));
+    assert!(matches!(Md5::resume(state(64, 64)), Err(hash_state::Error::InvalidState)));
+    assert!(matches!(Md5::resume(state(200, 200)), Err(hash_state::Error::InvalidState)));
+    let mut hs = match streamsha::Sha256::new().pause() {
+        HashState::Sha256(hs) => hs,
+        _ => unreachable!(),
+    };
+    hs.block_len = 64;
+    hs.message_len = 64;
+    assert!(matches!(streamsha::Sha256::resume(HashState::Sha256(hs)), Err(hash_state::Error::InvalidState)));
+}
+
+#[test]
+fn it_can_update_vectored() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 13) as u8).collect();
+    // slices crossing block boundaries, including empty ones
+    let lens = [0, 1, 63, 64, 65, 0, 127, 128, 129, 3, 0];
+    let mut bufs: Vec<&[u8]> = Vec::new();
+    let mut rest = &data[..];
+    for len in lens.iter().copied() {
+        let (buf, tail) = rest.split_at(len);
+        bufs.push(buf);
+        rest = tail;
+    }
+    bufs.push(rest);
+
+    let mut hasher = streamsha::Sha256::new();
+    assert_eq!(hasher.update_vectored(&bufs), data.len());
+    assert_eq!(hasher.finish(), streamsha::sha256(&data));
+    let mut hasher = streamsha::Sha512::new();
+    hasher.update(&data[..7]);
+    assert_eq!(hasher.update_vectored(&bufs[..5]), 193);
+    hasher.update(&data[200..]);
+    let mut expected = streamsha::Sha512::new();
+    expected.update(&data[..7]);
+    expected.update(&data[..193]);
+    expected.update(&data[200..]);
+    assert_eq!(hasher.finish(), expected.finish());
+    let mut hasher = Md5::new();
+    hasher.update_vectored(&bufs[..9]);
+    let mut hasher = Md5::resume(hasher.pause()).unwrap();
+    hasher.update_vectored(&bufs[9..]);
+    let mut expected = Md5::new();
+    expected.update(&data);
+    assert_eq!(hasher.finish(), expected.finish());
+}
@@ -0,0 +1,83 @@
+use streamsha::hash_state::{self, HashState};
+use streamsha::policy::Purpose;
+use streamsha::traits::{LengthLimitExceeded, Resumable, StreamHasher};
+use streamsha::*;
+
+fn data() -> Vec<u8> {
+    (0..1000).map(|i| (i * 13) as u8).collect()
+}
+
+#[test]
+fn it_hashes_with_all_algorithms() {
+    let data = data();
+    // SHA-1 is allowed for key identifiers under every policy
+    let mut hasher = MultiHasher::with_purpose(&Algorithm::ALL, Purpose::KeyIdentifier).unwrap();
+    hasher.update(&data[..100]);
+    hasher.update_vectored(&[&data[100..300], &data[300..]]);
+    let digests = hasher.finish();
+    assert_eq!(digests.sha1, Some(sha1(&data)));
+    assert_eq!(digests.sha256, Some(sha256(&data)));
+    assert_eq!(digests.sha512, Some(sha512(&data)));
+    assert_eq!(digests.get(Algorithm::Sha512), Some(&sha512(&data)[..]));
+}
+
+#[test]
+fn it_hashes_with_selected_algorithms() {
+    let hasher = MultiHasher::with_purpose(&[Algorithm::Sha512, Algorithm::Sha1, Algorithm::Sha1], Purpose::KeyIdentifier).unwrap();
+    assert!(hasher.contains(Algorithm::Sha1));
+    assert!(!hasher.contains(Algorithm::Sha256));
+    assert_eq!(hasher.algorithms().collect::<Vec<_>>(), [Algorithm::Sha1, Algorithm::Sha512]);
+    let digests = hasher.finish();
+    assert_eq!(digests.sha1, Some(sha1(b"")));
+    assert_eq!(digests.sha256, None);
+    assert_eq!(digests.get(Algorithm::Sha256), None);
+
+    let digests = MultiHasher::new(&[]).unwrap().finish();
+    assert_eq!(digests, MultiDigest { sha1: None, sha256: None, sha512: None });
+}
+
+#[test]
+fn it_can_pause_and_resume_as_a_whole() {
+    let data = data();
+    for i in (0..data.len()).step_by(37) {
+        let mut hasher = MultiHasher::new(&[Algorithm::Sha256, Algorithm::Sha512]).unwrap();
+        hasher.update(&data[..i]);
+        let state = hasher.pause();
+        assert!(matches!(state, HashState::Multi(ref hs) if hs.sha1.is_none() && hs.sha256.is_some()));
+        let mut hasher = MultiHasher::resume(state).unwrap();
+        hasher.update(&data[i..]);
+        let digests = hasher.finish();
+        assert_eq!(digests.sha256, Some(sha256(&data)));
+        assert_eq!(digests.sha512, Some(sha512(&data)));
+    }
+}
+
+#[test]
+fn it_rejects_other_hash_state() {
+    let state = Sha256::new().pause();
+    assert!(matches!(MultiHasher::resume(state), Err(hash_state::Error::HashTypeNotMatch)));
+    let state = MultiHasher::new(&[Algorithm::Sha256]).unwrap().pause();
+    assert!(Algorithm::from_hash_state(&state).is_none());
+    assert!(matches!(AnyHasher::resume(state), Err(hash_state::Error::HashTypeNotMatch)));
+}
+
+#[test]
+fn it_refuses_update_exceeding_any_limit() {
+    let mut hasher = MultiHasher::with_purpose(&[Algorithm::Sha1, Algorithm::Sha512], Purpose::KeyIdentifier).unwrap();
+    assert_eq!(hasher.try_update(b"abc"), Ok(3));
+    let state = match hasher.pause() {
+        HashState::Multi(mut hs) => {
+            // SHA-1 is at its limit, SHA-512 is not
+            let sha1 = hs.sha1.as_mut().unwrap();
+            sha1.message_len = u64::MAX / 8;
+            sha1.block_len = (sha1.message_len % 64) as usize;
+            HashState::Multi(hs)
+        }
+        _ => unreachable!(),
+    };
+    let mut hasher = MultiHasher::resume_with_purpose(state, Purpose::KeyIdentifier).unwrap();
+    assert_eq!(hasher.try_update(b"a"), Err(LengthLimitExceeded));
+    assert_eq!(hasher.try_update(b""), Ok(0));
+    hasher.update(b"a");
+    assert!(hasher.try_finish().is_err());
+}
@@ -0,0 +1,58 @@
+use streamsha::*;
+use streamsha::traits::{
+    StreamHasher, Resumable
+};
+use hex_literal::hex;
+
+fn messages() -> Vec<Vec<u8>> {
+    (0..21).map(|i| (0..i * 37).map(|j| (i + j) as u8).collect()).collect()
+}
+fn sha256(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    hasher.finish()
+}
+#[test]
+fn it_can_hash_many() {
+    let messages = messages();
+    let inputs: Vec<&[u8]> = messages.iter().map(|m| &m[..]).collect();
+    let mut outputs = vec![[0; 32]; inputs.len()];
+    hash_many(&inputs, &mut outputs);
+    for (message, output) in messages.iter().zip(outputs.iter()) {
+        assert_eq!(output, &sha256(message));
+    }
+}
+#[test]
+fn it_hashes_lanes_independently() {
+    let mut hasher = Sha256x8::new();
+    hasher.update([b"abc", &[], &[0; 55], &[0; 56], &[0; 57], &[0; 64], &[0x41; 1000], &[0x55; 1005]]);
+    let hashes = hasher.finish();
+    assert_eq!(hashes[0], hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+    assert_eq!(hashes[1], hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+    assert_eq!(hashes[2], hex!("02779466 cdec1638 11d07881 5c633f21 90141308 1449002f 24aa3e80 f0b88ef7"));
+    assert_eq!(hashes[3], hex!("d4817aa5 497628e7 c77e6b60 6107042b bba31308 88c5f47a 375e6179 be789fbb"));
+    assert_eq!(hashes[4], hex!("65a16cb7 861335d5 ace3c607 18b5052e 44660726 da4cd13b b745381b 235a1785"));
+    assert_eq!(hashes[5], hex!("f5a5fd42 d16a2030 2798ef6e d309979b 43003d23 20d9f0e8 ea9831a9 2759fb4b"));
+    assert_eq!(hashes[6], hex!("c2e68682 3489ced2 017f6059 b8b23931 8b6364f6 dcd835d0 a519105a 1eadd6e4"));
+    assert_eq!(hashes[7], hex!("f4d62dde c0f3dd90 ea1380fa 16a5ff8d c4c54b21 740650f2 4afc4120 903552b0"));
+}
+#[test]
+fn it_can_pause_lanes() {
+    let messages = messages();
+    let mut hasher = Sha256x4::new();
+    hasher.update([&messages[20][..100], &messages[19][..3], &messages[18][..], &messages[17][..64]]);
+    let [state0, state1, state2, state3] = hasher.pause();
+
+    // each lane can be resumed as a normal hasher
+    let mut hasher0 = Sha256::resume(state0).unwrap();
+    hasher0.update(&messages[20][100..]);
+    assert_eq!(hasher0.finish(), sha256(&messages[20]));
+
+    let mut hasher = Sha256x4::resume([state1, state2, state3, Sha256::new().pause()]).unwrap();
+    hasher.update([&messages[19][3..], &[], &messages[17][64..], b"abc"]);
+    let hashes = hasher.finish();
+    assert_eq!(hashes[0], sha256(&messages[19]));
+    assert_eq!(hashes[1], sha256(&messages[18]));
+    assert_eq!(hashes[2], sha256(&messages[17]));
+    assert_eq!(hashes[3], sha256(b"abc"));
+}
@@ -0,0 +1,57 @@
+use hex_literal::hex;
+use streamsha::traits::StreamHasher;
+use streamsha::{sha1, sha224, sha256, sha384, sha512, sha512_224, sha512_256, Sha1, Sha256, Sha512};
+
+const ABC_SHA1: [u8; 20] = sha1(b"abc");
+const ABC_SHA224: [u8; 28] = sha224(b"abc");
+const ABC_SHA256: [u8; 32] = sha256(b"abc");
+const ABC_SHA384: [u8; 48] = sha384(b"abc");
+const ABC_SHA512: [u8; 64] = sha512(b"abc");
+const ABC_SHA512_224: [u8; 28] = sha512_224(b"abc");
+const ABC_SHA512_256: [u8; 32] = sha512_256(b"abc");
+
+#[test]
+fn it_evaluates_in_const_context() {
+    assert_eq!(ABC_SHA1, hex!("a9993e364706816aba3e25717850c26c9cd0d89d"));
+    assert_eq!(ABC_SHA224, hex!("23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"));
+    assert_eq!(ABC_SHA256, hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+    assert_eq!(
+        ABC_SHA384,
+        hex!("cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7")
+    );
+    assert_eq!(
+        ABC_SHA512,
+        hex!("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f")
+    );
+    assert_eq!(ABC_SHA512_224, hex!("4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa"));
+    assert_eq!(ABC_SHA512_256, hex!("53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23"));
+}
+
+#[test]
+fn it_matches_stream_hashers() {
+    let data: Vec<u8> = (0..600).map(|i| (i * 7 + 3) as u8).collect();
+    // every length around the padding boundaries of both block sizes
+    for len in 0..data.len() {
+        let msg = &data[..len];
+        let mut hasher = Sha1::new();
+        hasher.update(msg);
+        assert_eq!(sha1(msg), hasher.finish(), "len = {}", len);
+        let mut hasher = Sha256::new();
+        hasher.update(msg);
+        assert_eq!(sha256(msg), hasher.finish(), "len = {}", len);
+        let mut hasher = Sha512::new();
+        hasher.update(msg);
+        assert_eq!(sha512(msg), hasher.finish(), "len = {}", len);
+    }
+}
+
+#[test]
+fn it_can_hash_empty_message() {
+    assert_eq!(sha224(b""), hex!("d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f"));
+    assert_eq!(
+        sha384(b""),
+        hex!("38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b")
+    );
+    assert_eq!(sha512_224(b""), hex!("6ed0dd02806fa89e25de060c19d3ac86cabb87d6a0ddd05c333b84f4"));
+    assert_eq!(sha512_256(b""), hex!("c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a"));
+}
@@ -0,0 +1,111 @@
+use streamsha::output::{self, DecodeError, Digest, Encoding};
+use streamsha::traits::StreamHasher;
+use streamsha::{Sha1, Sha256};
+
+const ABC: Digest<Sha256> =
+    Digest::new(output::hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+
+fn abc_digest() -> Digest<Sha256> {
+    let mut hasher = Sha256::new();
+    hasher.update(b"abc");
+    hasher.finish_digest()
+}
+
+#[test]
+fn it_formats_as_hex() {
+    let digest = abc_digest();
+    assert_eq!(digest, ABC);
+    assert_eq!(digest.to_string(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    assert_eq!(format!("{:X}", digest), "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD");
+    assert_eq!(
+        format!("{:?}", digest),
+        "Digest(ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad)"
+    );
+}
+
+#[test]
+fn it_parses_hex() {
+    let digest: Digest<Sha256> = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD".parse().unwrap();
+    assert_eq!(digest, ABC);
+    assert_eq!("ba78".parse::<Digest<Sha256>>(), Err(DecodeError::InvalidLength));
+    assert_eq!(
+        "zz7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".parse::<Digest<Sha256>>(),
+        Err(DecodeError::InvalidCharacter)
+    );
+}
+
+#[test]
+fn it_encodes_rfc4648_test_vectors() {
+    let vectors: &[(&[u8], &str, &str)] = &[
+        (b"", "", ""),
+        (b"f", "MY======", "Zg=="),
+        (b"fo", "MZXQ====", "Zm8="),
+        (b"foo", "MZXW6===", "Zm9v"),
+        (b"foob", "MZXW6YQ=", "Zm9vYg=="),
+        (b"fooba", "MZXW6YTB", "Zm9vYmE="),
+        (b"foobar", "MZXW6YTBOI======", "Zm9vYmFy"),
+    ];
+    let mut buf = [0u8; 32];
+    let mut out = [0u8; 6];
+    for (input, base32, base64) in vectors {
+        assert_eq!(output::encode(Encoding::Base32, input, &mut buf).unwrap(), *base32);
+        assert_eq!(output::encode(Encoding::Base64, input, &mut buf).unwrap(), *base64);
+        assert_eq!(Encoding::Base32.encoded_len(input.len()), base32.len());
+        assert_eq!(Encoding::Base64.encoded_len(input.len()), base64.len());
+        let out = &mut out[..input.len()];
+        output::decode(Encoding::Base32, base32, out).unwrap();
+        assert_eq!(out, *input);
+        output::decode(Encoding::Base64, base64, out).unwrap();
+        assert_eq!(out, *input);
+    }
+    let out = &mut out[..1];
+    assert_eq!(output::decode(Encoding::Hex, "ab==", out), Err(DecodeError::InvalidLength));
+    assert_eq!(output::decode(Encoding::Base32, "MY=====", out), Err(DecodeError::InvalidLength));
+    assert_eq!(output::decode(Encoding::Base32, "MY=======", out), Err(DecodeError::InvalidLength));
+    assert_eq!(output::decode(Encoding::Base64, "Zg=", out), Err(DecodeError::InvalidLength));
+    assert_eq!(output::decode(Encoding::Base64, "Zg===", out), Err(DecodeError::InvalidLength));
+    assert_eq!(output::decode(Encoding::Base64, "Zg", out), Ok(()));
+}
+
+#[test]
+fn it_round_trips_encodings() {
+    let digest = abc_digest();
+    let mut buf = [0u8; 128];
+    for &encoding in &[Encoding::Hex, Encoding::Base32, Encoding::Base64, Encoding::Base64Url] {
+        let encoded = digest.encode_into(encoding, &mut buf).unwrap();
+        assert_eq!(Digest::<Sha256>::decode(encoding, encoded), Ok(digest));
+        let encoded = digest.encode_multibase_into(encoding, &mut buf).unwrap();
+        assert_eq!(Digest::<Sha256>::decode_multibase(encoded), Ok(digest));
+    }
+    assert_eq!(
+        digest.encode_into(Encoding::Base64Url, &mut buf).unwrap(),
+        "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0"
+    );
+    assert_eq!(
+        digest.encode_multibase_into(Encoding::Base32, &mut buf).unwrap(),
+        "bxj4bnp4pahh6uqkbidpf3lrceoyagyndsylxvhfucd7wd4qacwwq"
+    );
+    assert_eq!(Digest::<Sha256>::decode_multibase("xabc"), Err(DecodeError::UnknownPrefix));
+    let hex = digest.to_string();
+    let padded = format!("{}==", hex);
+    assert_eq!(Digest::<Sha256>::decode(Encoding::Hex, &padded), Err(DecodeError::InvalidLength));
+    let base64 = digest.encode_into(Encoding::Base64, &mut buf).unwrap();
+    let unpadded = base64.trim_end_matches('=');
+    assert_eq!(Digest::<Sha256>::decode(Encoding::Base64, unpadded), Ok(digest));
+    let over_padded = format!("{}=", base64);
+    assert_eq!(Digest::<Sha256>::decode(Encoding::Base64, &over_padded), Err(DecodeError::InvalidLength));
+    let over_padded = format!("{}=", digest.encode_into(Encoding::Base64Url, &mut buf).unwrap());
+    assert_eq!(Digest::<Sha256>::decode(Encoding::Base64Url, &over_padded), Err(DecodeError::InvalidLength));
+    assert!(digest.encode_into(Encoding::Hex, &mut buf[..63]).is_err());
+}
+
+#[test]
+fn it_compares_with_bytes() {
+    let digest = abc_digest();
+    assert!(digest == streamsha::sha256(b"abc")[..]);
+    assert!(digest != streamsha::sha256(b"abd")[..]);
+    assert!(!output::ct_eq(&[1, 2], &[1, 2, 3]));
+    let mut hasher = Sha1::new();
+    hasher.update(b"abc");
+    assert_eq!(hasher.finish_digest().as_bytes(), streamsha::sha1(b"abc"));
+}
@@ -0,0 +1,145 @@
+use streamsha::build_hasher::StreamshaBuildHasher;
+use streamsha::hash_state;
+use streamsha::md::{Endianness, MdCompression, MdHasher};
+use streamsha::policy::{self, Policy, PolicyViolation, Purpose};
+use streamsha::traits::Resumable;
+use streamsha::{Algorithm, AnyHasher, MultiHasher, Sha1, Sha1Compression, Sha256, Sha512};
+
+/// A toy compression function, standing in for non-approved legacy hashes
+struct XorCompression;
+
+impl MdCompression for XorCompression {
+    type Word = u32;
+    type State = [u32; 1];
+    type Block = [u8; 64];
+    type Output = [u8; 4];
+    const ID: u64 = 0x584f_5200_0000;
+    const IV: [u32; 1] = [0];
+    const EMPTY_BLOCK: [u8; 64] = [0; 64];
+    const BLOCK_SIZE: usize = 64;
+    const LENGTH_SIZE: usize = 8;
+    const ENDIANNESS: Endianness = Endianness::Big;
+    const MAX_MESSAGE_LEN: u128 = u64::MAX as u128 / 8;
+    fn compress(state: &mut [u32; 1], blocks: &[u8]) {
+        for word in blocks.chunks(4) {
+            state[0] ^= u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+    }
+    fn output(state: &[u32; 1]) -> [u8; 4] {
+        state[0].to_be_bytes()
+    }
+}
+
+#[test]
+fn policy_allows() {
+    for &purpose in &Purpose::ALL {
+        for &algorithm in &Algorithm::ALL {
+            assert!(Policy::Unrestricted.allows(Some(algorithm), purpose));
+        }
+        assert!(Policy::Unrestricted.allows(None, purpose));
+        assert!(Policy::NoSha1Signatures.allows(None, purpose));
+        assert!(!Policy::FipsApproved.allows(None, purpose));
+        assert!(Policy::FipsApproved.allows(Some(Algorithm::Sha256), purpose));
+        assert!(Policy::FipsApproved.allows(Some(Algorithm::Sha512), purpose));
+        assert_eq!(
+            Policy::NoSha1Signatures.allows(Some(Algorithm::Sha1), purpose),
+            purpose != Purpose::DigitalSignature
+        );
+        assert_eq!(
+            Policy::FipsApproved.allows(Some(Algorithm::Sha1), purpose),
+            purpose == Purpose::KeyIdentifier || purpose == Purpose::Mac
+        );
+    }
+}
+
+#[test]
+fn policy_check() {
+    let violation = Policy::FipsApproved.check(Some(Algorithm::Sha1), Purpose::Integrity).unwrap_err();
+    assert_eq!(
+        violation,
+        PolicyViolation {
+            policy: Policy::FipsApproved,
+            purpose: Purpose::Integrity,
+            algorithm: Some(Algorithm::Sha1),
+        }
+    );
+    assert_eq!(violation.to_string(), "SHA-1 is not allowed for Integrity under FipsApproved policy");
+    // HMAC-SHA-1 is still approved
+    assert!(Policy::FipsApproved.check(Some(Algorithm::Sha1), Purpose::Mac).is_ok());
+    // without a purpose, the strictest one is reported
+    assert_eq!(
+        Policy::NoSha1Signatures.check_any(Some(Algorithm::Sha1)).unwrap_err().purpose,
+        Purpose::DigitalSignature
+    );
+    assert!(Policy::FipsApproved.check_any(Some(Algorithm::Sha256)).is_ok());
+    assert_eq!(
+        Policy::FipsApproved.check(None, Purpose::Integrity).unwrap_err().to_string(),
+        "custom hash is not allowed for Integrity under FipsApproved policy"
+    );
+}
+
+/// The global policy is only changed here, as tests run in parallel.
+#[test]
+fn with_purpose() {
+    assert_eq!(policy::current(), Policy::FLOOR);
+    for &policy in &[Policy::Unrestricted, Policy::NoSha1Signatures, Policy::FipsApproved] {
+        policy::set(policy);
+        let current = policy.max(Policy::FLOOR);
+        assert_eq!(policy::current(), current);
+        for &purpose in &Purpose::ALL {
+            for &algorithm in &Algorithm::ALL {
+                let allowed = current.allows(Some(algorithm), purpose);
+                match AnyHasher::with_purpose(algorithm, purpose) {
+                    Ok(hasher) => {
+                        assert!(allowed);
+                        assert_eq!(hasher.algorithm(), algorithm);
+                    }
+                    Err(e) => {
+                        assert!(!allowed);
+                        assert_eq!(e.algorithm, Some(algorithm));
+                        assert_eq!(e.policy, current);
+                    }
+                }
+            }
+            assert_eq!(Sha1::with_purpose(purpose).is_ok(), current.allows(Some(Algorithm::Sha1), purpose));
+            assert_eq!(Sha256::with_purpose(purpose).is_ok(), current.allows(Some(Algorithm::Sha256), purpose));
+            assert_eq!(Sha512::with_purpose(purpose).is_ok(), current.allows(Some(Algorithm::Sha512), purpose));
+            assert_eq!(MdHasher::<XorCompression>::with_purpose(purpose).is_ok(), current.allows(None, purpose));
+            let allowed = current.allows(Some(Algorithm::Sha1), purpose);
+            assert_eq!(Sha1::resume_with_purpose(Sha1::new().pause(), purpose).is_ok(), allowed);
+            assert_eq!(AnyHasher::resume_with_purpose(Sha1::new().pause(), purpose).is_ok(), allowed);
+            assert_eq!(MultiHasher::with_purpose(&[Algorithm::Sha1, Algorithm::Sha256], purpose).is_ok(), allowed);
+            let state = MultiHasher::with_purpose(&[Algorithm::Sha1], Purpose::KeyIdentifier).unwrap().pause();
+            assert_eq!(MultiHasher::resume_with_purpose(state, purpose).is_ok(), allowed);
+        }
+        // constructors without a purpose require the hash to be allowed for every purpose
+        for &algorithm in &Algorithm::ALL {
+            let allowed = current.check_any(Some(algorithm)).is_ok();
+            assert_eq!(AnyHasher::new(algorithm).is_ok(), allowed);
+            assert_eq!(MultiHasher::new(&[algorithm]).is_ok(), allowed);
+            // like `new`, `resume` is unchecked
+            assert_eq!(AnyHasher::resume(algorithm.initial_state()).unwrap().algorithm(), algorithm);
+        }
+        assert!(Sha1::resume(Sha1::new().pause()).is_ok());
+        let state = MultiHasher::with_purpose(&[Algorithm::Sha1], Purpose::KeyIdentifier).unwrap().pause();
+        assert!(MultiHasher::resume(state).is_ok());
+        assert!(MdHasher::<XorCompression>::resume(MdHasher::<XorCompression>::new().pause()).is_ok());
+        // the hash state type is checked before the policy
+        assert!(matches!(
+            Sha1::resume_with_purpose(Sha256::new().pause(), Purpose::DigitalSignature),
+            Err(hash_state::Error::HashTypeNotMatch)
+        ));
+        assert_eq!(
+            StreamshaBuildHasher::<Sha1Compression>::try_new().is_ok(),
+            current.allows(Some(Algorithm::Sha1), Purpose::Integrity)
+        );
+        assert_eq!(
+            StreamshaBuildHasher::<Sha1Compression>::try_with_key(b"key").is_ok(),
+            current.allows(Some(Algorithm::Sha1), Purpose::Mac)
+        );
+        let panicked = std::panic::catch_unwind(StreamshaBuildHasher::<Sha1Compression>::new).is_err();
+        assert_eq!(panicked, !current.allows(Some(Algorithm::Sha1), Purpose::Integrity));
+    }
+    policy::set(Policy::Unrestricted);
+    assert_eq!(policy::current(), Policy::FLOOR);
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "digest")]
+use digest::const_oid::AssociatedOid;
+use digest::Digest;
+use hex_literal::hex;
+use hmac::{Hmac, Mac, SimpleHmac};
+use streamsha::traits::{Resumable, StreamHasher};
+use streamsha::*;
+
+fn digest_of<D: Digest>(data: &[u8]) -> Vec<u8> {
+    D::digest(data).to_vec()
+}
+
+#[test]
+fn it_implements_digest() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 13) as u8).collect();
+    assert_eq!(digest_of::<Sha1>(&data), sha1(&data));
+    assert_eq!(digest_of::<Sha256>(&data), sha256(&data));
+    assert_eq!(digest_of::<Sha512>(&data), sha512(&data));
+
+    let mut hasher = <Sha256 as Digest>::new();
+    Digest::update(&mut hasher, &data[..100]);
+    assert_eq!(hasher.finalize_reset()[..], sha256(&data[..100]));
+    Digest::update(&mut hasher, &data);
+    assert_eq!(hasher.finalize()[..], sha256(&data));
+}
+
+#[test]
+fn it_can_finalize_resumed_hasher() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 13) as u8).collect();
+    let mut hasher = Sha512::new();
+    StreamHasher::update(&mut hasher, &data[..333]);
+    let mut hasher = Sha512::resume(hasher.pause()).unwrap();
+    Digest::update(&mut hasher, &data[333..]);
+    assert_eq!(hasher.finalize()[..], sha512(&data)[..]);
+}
+
+#[test]
+fn it_works_with_hmac() {
+    // RFC 4231 test case 2
+    let mut mac = SimpleHmac::<Sha256>::new_from_slice(b"Jefe").unwrap();
+    mac.update(b"what do ya want for nothing?");
+    assert_eq!(
+        mac.finalize().into_bytes()[..],
+        hex!("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+    );
+}
+
+#[test]
+fn it_works_with_core_based_hmac() {
+    // RFC 4231 test case 2
+    let mut mac = Hmac::<rustcrypto::Sha256Digest>::new_from_slice(b"Jefe").unwrap();
+    mac.update(b"what do ya want for nothing?");
+    assert_eq!(
+        mac.finalize().into_bytes()[..],
+        hex!("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+    );
+
+    let key: Vec<u8> = (0..200).map(|i| (i * 7) as u8).collect();
+    let data: Vec<u8> = (0..1000).map(|i| (i * 13) as u8).collect();
+    for &(key_len, data_len) in &[(0, 0), (20, 55), (64, 64), (128, 129), (200, 1000)] {
+        let mut mac = Hmac::<rustcrypto::Sha1Digest>::new_from_slice(&key[..key_len]).unwrap();
+        let mut simple = SimpleHmac::<Sha1>::new_from_slice(&key[..key_len]).unwrap();
+        mac.update(&data[..data_len]);
+        simple.update(&data[..data_len]);
+        assert_eq!(mac.finalize().into_bytes(), simple.finalize().into_bytes());
+        let mut mac = Hmac::<rustcrypto::Sha512Digest>::new_from_slice(&key[..key_len]).unwrap();
+        let mut simple = SimpleHmac::<Sha512>::new_from_slice(&key[..key_len]).unwrap();
+        mac.update(&data[..data_len]);
+        simple.update(&data[..data_len]);
+        assert_eq!(mac.finalize().into_bytes(), simple.finalize().into_bytes());
+    }
+}
+
+#[test]
+fn it_works_with_pbkdf2() {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<rustcrypto::Sha256Digest>(b"password", b"salt", 2, &mut key);
+    assert_eq!(key, hex!("ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43"));
+}
+
+#[test]
+fn it_implements_core_wrappers() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 13) as u8).collect();
+    for len in [0, 1, 55, 56, 63, 64, 65, 111, 112, 127, 128, 129, 1000] {
+        assert_eq!(digest_of::<rustcrypto::Sha1Digest>(&data[..len]), sha1(&data[..len]));
+        assert_eq!(digest_of::<rustcrypto::Sha256Digest>(&data[..len]), sha256(&data[..len]));
+        assert_eq!(digest_of::<rustcrypto::Sha512Digest>(&data[..len]), sha512(&data[..len]));
+    }
+    let mut hasher = rustcrypto::Sha256Digest::new();
+    Digest::update(&mut hasher, &data[..100]);
+    assert_eq!(hasher.finalize_reset()[..], sha256(&data[..100]));
+    Digest::update(&mut hasher, &data);
+    assert_eq!(hasher.finalize()[..], sha256(&data));
+}
+
+#[test]
+fn it_has_oids() {
+    assert_eq!(Sha1::OID.to_string(), Algorithm::Sha1.oid());
+    assert_eq!(Sha256::OID.to_string(), Algorithm::Sha256.oid());
+    assert_eq!(Sha512::OID.as_bytes(), Algorithm::Sha512.oid_der());
+}
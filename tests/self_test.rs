@@ -0,0 +1,56 @@
+use streamsha::self_test::{Failure, SelfTestError};
+use streamsha::*;
+
+#[test]
+fn it_passes_self_tests() {
+    assert_eq!(self_test(), Ok(()));
+    assert_eq!(Sha1::self_test(), Ok(()));
+    assert_eq!(Sha256::self_test(), Ok(()));
+    assert_eq!(Sha512::self_test(), Ok(()));
+    for algorithm in Algorithm::ALL.iter() {
+        assert_eq!(algorithm.self_test(), Ok(()));
+    }
+}
+
+#[test]
+fn it_describes_failures() {
+    let error = SelfTestError {
+        algorithm: Algorithm::Sha256,
+        failure: Failure::KnownAnswer { index: 3 },
+    };
+    assert_eq!(error.to_string(), "SHA-256 known-answer test #3 failed");
+    let error = SelfTestError {
+        algorithm: Algorithm::Sha1,
+        failure: Failure::PauseResume,
+    };
+    assert_eq!(error.to_string(), "SHA-1 pause/resume test failed");
+}
+
+#[cfg(feature = "self-test")]
+#[test]
+fn it_runs_self_tests_on_first_construction() {
+    use streamsha::traits::StreamHasher;
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            std::thread::spawn(|| {
+                let mut hasher = Sha256::default();
+                hasher.update(b"abc");
+                hasher.finish()
+            })
+        })
+        .collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), sha256(b"abc"));
+    }
+    assert_eq!(AnyHasher::new(Algorithm::Sha512).unwrap().algorithm(), Algorithm::Sha512);
+}
+
+#[cfg(feature = "self-test")]
+#[test]
+fn it_keeps_constructors_const() {
+    use streamsha::traits::StreamHasher;
+    const SHA256: Sha256 = Sha256::new();
+    const SHA1: Sha1 = Sha1::new();
+    assert_eq!(SHA256.finish(), sha256(b""));
+    assert_eq!(SHA1.finish(), sha1(b""));
+}
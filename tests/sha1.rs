@@ -1,9 +1,10 @@
 use streamsha::*;
 use streamsha::traits::{
-    StreamHasher, Resumable
+    StreamHasher
 };
 use hex_literal::hex;
 
+#[allow(non_upper_case_globals)]
 const vectors: &[(&[u8], [u8;20])] = &[
     (b"abc", hex!("A9993E36 4706816A BA3E2571 7850C26C 9CD0D89D")),
     (b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq", hex!("84983E44 1C3BD26E BAAE4AA1 F95129E5 E54670F1"))
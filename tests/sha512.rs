@@ -1,9 +1,10 @@
 use streamsha::*;
 use streamsha::traits::{
-    StreamHasher, Resumable
+    StreamHasher
 };
 use hex_literal::hex;
 
+#[allow(non_upper_case_globals)]
 const vectors: &[(&[u8], &[u8])] = &[
     (&[], &hex!("cf83e1357eefb8bd f1542850d66d8007 d620e4050b5715dc 83f4a921d36ce9ce 47d0d13c5d85f2b0 ff8318d2877eec2f 63b931bd47417a81 a538327af927da3e")),
     (&[0; 111], &hex!("77ddd3a542e530fd 047b8977c657ba6c e72f1492e360b2b2 212cd264e75ec038 82e4ff0525517ab4 207d14c70c2259ba 88d4d335ee0e7e20 543d22102ab1788c")),
@@ -25,6 +26,26 @@ fn it_can_hash_vectors() {
     }
 }
 #[test]
+fn it_can_hash_large_buffer_at_once() {
+    let data = vec![0x5a; 0x100000];
+    let mut hasher1 = Sha512::new();
+    hasher1.update(&data);
+    let mut hasher2 = Sha512::new();
+    for chunk in data.chunks(1000) {
+        hasher2.update(chunk);
+    }
+    assert_eq!(&hasher1.finish()[..], &hasher2.finish()[..])
+}
+#[test]
+fn it_can_update_blocks() {
+    let mut hasher = Sha512::new();
+    hasher.update(&[0x41; 104]);
+    let written_bytes = hasher.update_blocks(&[[0x41; 128]; 7]);
+    assert_eq!(written_bytes, 896);
+    let hash = hasher.finish();
+    assert_eq!(&hash[..], &hex!("329c52ac62d1fe73 1151f2b895a00475 445ef74f50b979c6 f7bb7cae349328c1 d4cb4f7261a0ab43 f936a24b000651d4 a824fcdd577f211a ef8f806b16afe8af")[..])
+}
+#[test]
 fn it_can_hash_0x20000000_z() {
     let mut hasher = Sha512::new();
     for _ in 0..0x100000 {
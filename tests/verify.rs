@@ -0,0 +1,50 @@
+use streamsha::output::Digest;
+use streamsha::verify::{DigestMismatch, Verifier};
+use streamsha::{sha256, Sha256};
+
+#[test]
+fn it_verifies_matching_data() {
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+    let mut verifier = Verifier::<Sha256>::new(Digest::new(sha256(&data)));
+    for chunk in data.chunks(7) {
+        verifier.update(chunk);
+    }
+    assert_eq!(verifier.verify(), Ok(()));
+}
+
+#[test]
+fn it_rejects_modified_data() {
+    let mut verifier = Verifier::<Sha256>::new(Digest::new(sha256(b"abc")));
+    verifier.update(b"abd");
+    assert_eq!(verifier.verify(), Err(DigestMismatch));
+}
+
+#[cfg(feature = "std")]
+mod reader {
+    use std::io::{self, Read};
+    use streamsha::output::Digest;
+    use streamsha::verify::VerifyingReader;
+    use streamsha::{sha256, Sha256};
+
+    #[test]
+    fn it_passes_matching_data_through() {
+        let data: Vec<u8> = (0..100000).map(|i| (i * 31) as u8).collect();
+        let mut reader = VerifyingReader::<_, Sha256>::new(&data[..], Digest::new(sha256(&data)));
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+        assert!(reader.is_verified());
+    }
+
+    #[test]
+    fn it_fails_at_eof_on_mismatch() {
+        let data = b"tampered";
+        let mut reader = VerifyingReader::<_, Sha256>::new(&data[..], Digest::new(sha256(b"original")));
+        let mut out = Vec::new();
+        let err = reader.read_to_end(&mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(!reader.is_verified());
+        // keeps failing
+        assert!(reader.read(&mut [0; 8]).is_err());
+    }
+}